@@ -0,0 +1,189 @@
+use actix_multipart::form::bytes::Bytes;
+use actix_multipart::form::json::Json as MultipartJson;
+use actix_multipart::form::MultipartForm;
+use actix_web::http::header::{CacheControl, CacheDirective, ContentDisposition};
+use actix_web::{get, post, web, HttpResponse, Responder};
+use common::app_error::AppError;
+use common::transfer::ResultVO;
+use futures_util::StreamExt;
+use services::coupon_task::coupon_task_service;
+use services::coupon_task_progress::ProgressEvent;
+use services::dto::task_req::{
+    TaskCreateReqDto, TaskIdReqDto, TaskPageQueryReqDto, TaskRescheduleReqDto,
+};
+use services::AppState;
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/merchant-admin/coupon-task")
+            .service(create_task_route)
+            .service(page_task_route)
+            .service(find_task_route)
+            .service(download_fail_file_route)
+            .service(fail_file_link_route)
+            .service(find_progress_route)
+            .service(watch_progress_route)
+            .service(cancel_task_route)
+            .service(reschedule_task_route)
+            .service(retry_task_route),
+    );
+}
+
+/// 创建分发任务表单，用户文件放在 `file` 字段中，任务信息以 JSON 放在 `task` 字段中
+#[derive(MultipartForm)]
+struct TaskCreateForm {
+    #[multipart(limit = "10MB")]
+    file: Bytes,
+    task: MultipartJson<TaskCreateReqDto>,
+}
+
+#[post("/create")]
+async fn create_task_route(
+    form: MultipartForm<TaskCreateForm>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let form = form.into_inner();
+    let task_id = coupon_task_service()
+        .create_task(
+            form.file.file_name,
+            form.file.data.into(),
+            form.task.into_inner(),
+            app_state,
+        )
+        .await?;
+
+    Ok(ResultVO::success_with("分发任务创建成功", task_id))
+}
+
+#[get("/page")]
+async fn page_task_route(
+    req: web::Query<TaskPageQueryReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let page = coupon_task_service()
+        .page_task(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(page))
+}
+
+#[get("/{id}")]
+async fn find_task_route(
+    path: web::Path<i64>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let task = coupon_task_service()
+        .find_task(path.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(task))
+}
+
+#[get("/{id}/fail-file")]
+async fn download_fail_file_route(
+    path: web::Path<i64>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let file = coupon_task_service()
+        .download_fail_file(path.into_inner(), app_state)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(file.content_type)
+        .insert_header(ContentDisposition::attachment(file.file_name))
+        .body(file.content))
+}
+
+/// 生成失败用户文件的临时下载链接，链接在有效期内不需要登录即可下载
+#[get("/{id}/fail-file/link")]
+async fn fail_file_link_route(
+    path: web::Path<i64>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let link = coupon_task_service()
+        .fail_file_link(path.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(link))
+}
+
+/// 查询任务进度快照
+///
+/// 与其他分发任务接口使用同一前缀，放在 `/{id}/progress` 下；`/{id}` 返回的任务详情中也包含各项数量
+#[get("/{id}/progress")]
+async fn find_progress_route(
+    path: web::Path<i64>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let progress = coupon_task_service()
+        .find_progress(path.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(progress))
+}
+
+/// 以 Server-Sent Events 推送任务进度，每条事件的 `data` 为进度 JSON，空闲时发送 `:` 心跳；
+/// 任务结束、尚未到发送时间或连接超过 30 分钟后关闭连接，客户端需要时重新连接
+#[get("/{id}/progress/stream")]
+async fn watch_progress_route(
+    path: web::Path<i64>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let progress = coupon_task_service()
+        .watch_progress(path.into_inner(), app_state)
+        .await?;
+    let events = progress.map(|event| {
+        let frame = match event {
+            ProgressEvent::Progress(progress) => format!(
+                "event: progress\ndata: {}\n\n",
+                serde_json::to_string(&progress).unwrap_or_default()
+            ),
+            // 以冒号开头的注释行，客户端会忽略
+            ProgressEvent::Heartbeat => ":\n\n".to_string(),
+        };
+        Ok::<_, actix_web::Error>(web::Bytes::from(frame))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        // 避免反向代理缓冲事件
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(events))
+}
+
+#[post("/cancel")]
+async fn cancel_task_route(
+    req: web::Json<TaskIdReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    coupon_task_service()
+        .cancel_task(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::<()>::success_with_message("分发任务取消成功"))
+}
+
+#[post("/reschedule")]
+async fn reschedule_task_route(
+    req: web::Json<TaskRescheduleReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    coupon_task_service()
+        .reschedule_task(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::<()>::success_with_message("发送时间修改成功"))
+}
+
+#[post("/retry")]
+async fn retry_task_route(
+    req: web::Json<TaskIdReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    coupon_task_service()
+        .retry_task(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::<()>::success_with_message("分发任务已开始重试"))
+}
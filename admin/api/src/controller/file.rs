@@ -0,0 +1,26 @@
+use actix_web::http::header::ContentDisposition;
+use actix_web::{web, HttpResponse};
+use common::app_error::AppError;
+use common::storage::local::DOWNLOAD_PATH;
+use services::dto::file_req::FileDownloadReqDto;
+use services::file::file_service;
+use services::AppState;
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource(DOWNLOAD_PATH).route(web::get().to(download_signed_route)));
+}
+
+/// 本地存储的临时下载链接，不需要登录
+async fn download_signed_route(
+    req: web::Query<FileDownloadReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let file = file_service()
+        .download_signed(req.into_inner(), app_state)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(file.content_type)
+        .insert_header(ContentDisposition::attachment(file.file_name))
+        .body(file.content))
+}
@@ -1 +1,21 @@
-pub mod template;
\ No newline at end of file
+use actix_multipart::form::MultipartFormConfig;
+
+pub mod coupon_task;
+pub mod file;
+pub mod template;
+pub mod user_coupon;
+pub mod user_popup;
+
+/// 上传文件在内存中的大小上限，不小于各表单文件字段的 `limit`
+const UPLOAD_MEMORY_LIMIT: usize = 10 * 1024 * 1024;
+/// 一个表单所有字段的大小上限，在文件之外留出其他字段的空间
+const UPLOAD_TOTAL_LIMIT: usize = UPLOAD_MEMORY_LIMIT + 1024 * 1024;
+
+/// multipart 表单配置
+///
+/// 默认配置只允许 2 MiB 的内存数据，超过时即使没有达到字段的 `limit` 也会被拒绝，调高到上传文件的大小上限
+pub fn multipart_config() -> MultipartFormConfig {
+    MultipartFormConfig::default()
+        .memory_limit(UPLOAD_MEMORY_LIMIT)
+        .total_limit(UPLOAD_TOTAL_LIMIT)
+}
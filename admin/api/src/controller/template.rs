@@ -1,12 +1,46 @@
-use actix_web::{post, web, Responder};
+use actix_multipart::form::bytes::Bytes;
+use actix_multipart::form::MultipartForm;
+use actix_web::http::header::{self, ContentDisposition};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
 use common::app_error::AppError;
 use common::transfer::ResultVO;
-use services::dto::template_req::TemplateSaveReqDto;
+use services::dto::page_req::PageReqDto;
+use services::dto::template_req::{
+    TemplateApproveReqDto, TemplateCopyReqDto, TemplateExportReqDto, TemplateIdReqDto,
+    TemplateImportReqDto, TemplateNumberReqDto, TemplatePageQueryReqDto, TemplatePreviewReqDto,
+    TemplateRejectReqDto, TemplateSaveReqDto, TemplateUpdateReqDto,
+};
 use services::template::template_service;
+use services::template_audit::template_audit_service;
+use services::template_export::{template_export_service, ExportFormat};
+use services::template_import::template_import_service;
+use services::template_log::template_log_service;
+use services::template_preview::template_preview_service;
+use services::template_statistics::template_statistics_service;
 use services::AppState;
 
 pub fn init(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/api/merchant-admin/coupon-template").service(create_template_route));
+    cfg.service(
+        web::scope("/api/merchant-admin/coupon-template")
+            .service(create_template_route)
+            .service(import_template_route)
+            .service(export_template_route)
+            .service(update_template_route)
+            .service(preview_template_route)
+            .service(page_template_route)
+            .service(increase_number_route)
+            .service(terminate_template_route)
+            .service(delete_template_route)
+            .service(restore_template_route)
+            .service(submit_template_route)
+            .service(approve_template_route)
+            .service(reject_template_route)
+            .service(shop_statistics_route)
+            .service(find_template_route)
+            .service(template_statistics_route)
+            .service(page_template_logs_route)
+            .service(copy_template_route),
+    );
 }
 
 #[post("/create")]
@@ -20,3 +54,281 @@ async fn create_template_route(
 
     Ok(ResultVO::success_with("模板创建成功", rows))
 }
+
+/// 导入文件表单，文件放在 `file` 字段中
+#[derive(MultipartForm)]
+struct TemplateImportForm {
+    #[multipart(limit = "10MB")]
+    file: Bytes,
+}
+
+#[post("/import")]
+async fn import_template_route(
+    form: MultipartForm<TemplateImportForm>,
+    req: web::Query<TemplateImportReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let file = form.into_inner().file;
+    let result = template_import_service()
+        .import_templates(
+            file.file_name,
+            file.data.to_vec(),
+            req.into_inner(),
+            app_state,
+        )
+        .await?;
+
+    let message = if result.fail_count == 0 {
+        "模板导入成功"
+    } else if result.success_count == 0 {
+        "模板导入失败"
+    } else {
+        "模板部分导入成功"
+    };
+    Ok(ResultVO::success_with(message, result))
+}
+
+#[get("/export")]
+async fn export_template_route(
+    http_req: HttpRequest,
+    req: web::Query<TemplatePageQueryReqDto>,
+    export: web::Query<TemplateExportReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let format = export.format.unwrap_or_else(|| {
+        let accept = http_req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok());
+        ExportFormat::from_accept(accept)
+    });
+    let file = template_export_service()
+        .export_templates(req.into_inner(), format, app_state)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(file.content_type)
+        .insert_header(ContentDisposition::attachment(file.file_name))
+        .streaming(file.body))
+}
+
+#[post("/update")]
+async fn update_template_route(
+    req: web::Json<TemplateUpdateReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let template = template_service()
+        .update_template(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with("模板修改成功", template))
+}
+
+#[post("/preview")]
+async fn preview_template_route(
+    req: web::Json<TemplatePreviewReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let preview = template_preview_service()
+        .preview_discount(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(preview))
+}
+
+#[post("/increase-number")]
+async fn increase_number_route(
+    req: web::Json<TemplateNumberReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let stock = template_service()
+        .increase_number(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with("库存增加成功", stock))
+}
+
+#[post("/terminate")]
+async fn terminate_template_route(
+    req: web::Json<TemplateIdReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    template_service()
+        .terminate_template(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::<()>::success_with_message("模板已结束"))
+}
+
+#[post("/delete")]
+async fn delete_template_route(
+    req: web::Json<TemplateIdReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    template_service()
+        .delete_template(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::<()>::success_with_message("模板删除成功"))
+}
+
+#[post("/restore")]
+async fn restore_template_route(
+    req: web::Json<TemplateIdReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    template_service()
+        .restore_template(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::<()>::success_with_message("模板恢复成功"))
+}
+
+#[post("/submit")]
+async fn submit_template_route(
+    req: web::Json<TemplateIdReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let template = template_audit_service()
+        .submit_template(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with("模板已提交审核", template))
+}
+
+#[post("/approve")]
+async fn approve_template_route(
+    req: web::Json<TemplateApproveReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let template = template_audit_service()
+        .approve_template(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with("模板审核通过", template))
+}
+
+#[post("/reject")]
+async fn reject_template_route(
+    req: web::Json<TemplateRejectReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let template = template_audit_service()
+        .reject_template(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with("模板已驳回", template))
+}
+
+#[get("/page")]
+async fn page_template_route(
+    req: web::Query<TemplatePageQueryReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let page = template_service()
+        .page_template(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(page))
+}
+
+#[get("/statistics")]
+async fn shop_statistics_route(app_state: web::Data<AppState>) -> Result<impl Responder, AppError> {
+    let statistics = template_statistics_service()
+        .shop_statistics(app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(statistics))
+}
+
+#[get("/{id}")]
+async fn find_template_route(
+    path: web::Path<i64>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let template = template_service()
+        .find_template(path.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(template))
+}
+
+#[get("/{id}/statistics")]
+async fn template_statistics_route(
+    path: web::Path<i64>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let statistics = template_statistics_service()
+        .template_statistics(path.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(statistics))
+}
+
+#[get("/{id}/logs")]
+async fn page_template_logs_route(
+    path: web::Path<i64>,
+    req: web::Query<PageReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let page = template_log_service()
+        .page_logs(path.into_inner(), req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(page))
+}
+
+/// 请求体可以省略，此时新模板的字段全部沿用源模板
+#[post("/{id}/copy")]
+async fn copy_template_route(
+    path: web::Path<i64>,
+    req: Option<web::Json<TemplateCopyReqDto>>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let req = req.map(web::Json::into_inner).unwrap_or_default();
+    let id = template_service()
+        .copy_template(path.into_inner(), req, app_state)
+        .await?;
+
+    Ok(ResultVO::success_with("模板复制成功", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::multipart_config;
+    use actix_web::{test, App};
+
+    async fn upload_size(form: MultipartForm<TemplateImportForm>) -> String {
+        form.into_inner().file.data.len().to_string()
+    }
+
+    #[actix_web::test]
+    async fn accepts_import_file_larger_than_default_memory_limit() {
+        let app = test::init_service(
+            App::new()
+                .app_data(multipart_config())
+                .route("/import", web::post().to(upload_size)),
+        )
+        .await;
+        let data = vec![b'x'; 3 * 1024 * 1024];
+        let mut body = b"--boundary\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"templates.csv\"\r\n\
+            Content-Type: text/csv\r\n\r\n"
+            .to_vec();
+        body.extend_from_slice(&data);
+        body.extend_from_slice(b"\r\n--boundary--\r\n");
+
+        let req = test::TestRequest::post()
+            .uri("/import")
+            .insert_header((
+                header::CONTENT_TYPE,
+                "multipart/form-data; boundary=boundary",
+            ))
+            .set_payload(body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert_eq!(test::read_body(resp).await, data.len().to_string());
+    }
+}
@@ -0,0 +1,22 @@
+use actix_web::{post, web, Responder};
+use common::app_error::AppError;
+use common::transfer::ResultVO;
+use services::dto::user_coupon_req::CouponClaimReqDto;
+use services::user_coupon::user_coupon_service;
+use services::AppState;
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/api/engine/user-coupon").service(claim_route));
+}
+
+#[post("/claim")]
+async fn claim_route(
+    req: web::Json<CouponClaimReqDto>,
+    app_state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let coupon = user_coupon_service()
+        .claim(req.into_inner(), app_state)
+        .await?;
+
+    Ok(ResultVO::success_with_data(coupon))
+}
@@ -0,0 +1,17 @@
+use actix_web::{post, web, Responder};
+use common::app_error::AppError;
+use common::transfer::ResultVO;
+use services::user_popup::user_popup_service;
+use services::AppState;
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/api/engine/user-popup").service(take_popups_route));
+}
+
+/// 取走当前用户待弹出的弹框消息，取走后不会再次返回
+#[post("/take")]
+async fn take_popups_route(app_state: web::Data<AppState>) -> Result<impl Responder, AppError> {
+    let popups = user_popup_service().take_popups(app_state).await?;
+
+    Ok(ResultVO::success_with_data(popups))
+}
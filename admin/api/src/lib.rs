@@ -4,11 +4,13 @@ use common::config::AppConfig;
 use log::{error, info};
 use middleware::error_handler::render_default_error;
 use sea_orm::Database;
+use services::coupon_task_executor::coupon_task_executor;
 use services::AppState;
 use std::sync::Arc;
 
 mod controller;
 mod middleware;
+mod scheduler;
 
 const MIDDLEWARE_LOG_PATTERN: &str = r#"%a "%r" %s %b "%{Referer}i" "%{User-Agent}i""#;
 const LOCAL_ADDRESS: &str = "127.0.0.1";
@@ -22,13 +24,38 @@ async fn start() -> std::io::Result<()> {
         .expect("Connect to database failed.");
     info!("Connect to database from url: {}", config.database.url);
 
+    let storage = common::storage::build(&config.storage).expect("Create file storage failed.");
+    info!("Create file storage: {:?}", storage);
+
     let app_state = web::Data::new(AppState {
         database: Arc::new(database),
+        config: Arc::new(config.clone()),
+        storage,
     });
 
+    match coupon_task_executor()
+        .resume_running(app_state.clone())
+        .await
+    {
+        Ok(0) => {}
+        Ok(resumed) => info!("Resume {} running coupon tasks", resumed),
+        Err(err) => error!("Resume running coupon tasks failed: {}", err),
+    }
+
+    let mut job_scheduler = if config.scheduler.enabled {
+        let job_scheduler = scheduler::start(&config.scheduler, app_state.clone())
+            .await
+            .expect("Start job scheduler failed.");
+        info!("Start job scheduler");
+        Some(job_scheduler)
+    } else {
+        None
+    };
+
     let app = HttpServer::new(move || {
         App::new()
             .app_data(app_state.clone())
+            .app_data(controller::multipart_config())
             .wrap(ErrorHandlers::new().default_handler(render_default_error))
             .wrap(Logger::new(MIDDLEWARE_LOG_PATTERN))
             .configure(controller_init)
@@ -36,11 +63,21 @@ async fn start() -> std::io::Result<()> {
     .bind((LOCAL_ADDRESS, config.server.port))?;
 
     app.run().await?;
+
+    if let Some(job_scheduler) = job_scheduler.as_mut() {
+        if let Err(err) = job_scheduler.shutdown().await {
+            error!("Shutdown job scheduler failed: {}", err);
+        }
+    }
     Ok(())
 }
 
 fn controller_init(cfg: &mut web::ServiceConfig) {
-    cfg.configure(controller::template::init);
+    cfg.configure(controller::template::init)
+        .configure(controller::coupon_task::init)
+        .configure(controller::file::init)
+        .configure(controller::user_coupon::init)
+        .configure(controller::user_popup::init);
 }
 
 pub fn main() {
@@ -0,0 +1,112 @@
+use actix_web::web;
+use common::config::SchedulerConfig;
+use log::{error, info};
+use services::coupon_task_executor::coupon_task_executor;
+use services::notify::notify_service;
+use services::template::template_service;
+use services::AppState;
+use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError};
+
+/// 创建并启动定时任务调度器
+///
+/// 返回的调度器需要在服务运行期间一直持有，服务停止时调用 `shutdown`
+pub async fn start(
+    config: &SchedulerConfig,
+    app_state: web::Data<AppState>,
+) -> Result<JobScheduler, JobSchedulerError> {
+    let scheduler = JobScheduler::new().await?;
+    scheduler
+        .add(expire_template_job(config, app_state.clone())?)
+        .await?;
+    scheduler
+        .add(dispatch_task_job(config, app_state.clone())?)
+        .await?;
+    scheduler
+        .add(cleanup_file_job(config, app_state.clone())?)
+        .await?;
+    scheduler.add(retry_notify_job(config, app_state)?).await?;
+    scheduler.start().await?;
+    Ok(scheduler)
+}
+
+/// 自动结束已过有效期的优惠券模板
+fn expire_template_job(
+    config: &SchedulerConfig,
+    app_state: web::Data<AppState>,
+) -> Result<Job, JobSchedulerError> {
+    let batch_size = config.expire_template_batch_size;
+    Job::new_async(config.expire_template_cron.as_str(), move |_, _| {
+        let app_state = app_state.clone();
+        Box::pin(async move {
+            match template_service()
+                .end_expired_templates(batch_size, app_state)
+                .await
+            {
+                Ok(0) => {}
+                Ok(ended) => info!("自动结束已过期的优惠券模板 {} 个", ended),
+                Err(err) => error!("自动结束已过期的优惠券模板失败: {}", err),
+            }
+        })
+    })
+}
+
+/// 开始执行已到发送时间的定时分发任务
+fn dispatch_task_job(
+    config: &SchedulerConfig,
+    app_state: web::Data<AppState>,
+) -> Result<Job, JobSchedulerError> {
+    let batch_size = config.dispatch_task_batch_size;
+    Job::new_async(config.dispatch_task_cron.as_str(), move |_, _| {
+        let app_state = app_state.clone();
+        Box::pin(async move {
+            match coupon_task_executor()
+                .dispatch_due_tasks(batch_size, app_state)
+                .await
+            {
+                Ok(0) => {}
+                Ok(started) => info!("开始执行到达发送时间的分发任务 {} 个", started),
+                Err(err) => error!("开始执行到达发送时间的分发任务失败: {}", err),
+            }
+        })
+    })
+}
+
+/// 清理已结束超过保留天数的分发任务文件
+fn cleanup_file_job(
+    config: &SchedulerConfig,
+    app_state: web::Data<AppState>,
+) -> Result<Job, JobSchedulerError> {
+    let batch_size = config.cleanup_file_batch_size;
+    Job::new_async(config.cleanup_file_cron.as_str(), move |_, _| {
+        let app_state = app_state.clone();
+        Box::pin(async move {
+            let retention_days = app_state.config.storage.retention_days;
+            match coupon_task_executor()
+                .cleanup_task_files(retention_days, batch_size, app_state)
+                .await
+            {
+                Ok(0) => {}
+                Ok(cleaned) => info!("清理 {} 个已结束分发任务的文件", cleaned),
+                Err(err) => error!("清理已结束分发任务的文件失败: {}", err),
+            }
+        })
+    })
+}
+
+/// 重试到达重试时间的失败通知
+fn retry_notify_job(
+    config: &SchedulerConfig,
+    app_state: web::Data<AppState>,
+) -> Result<Job, JobSchedulerError> {
+    let batch_size = config.retry_notify_batch_size;
+    Job::new_async(config.retry_notify_cron.as_str(), move |_, _| {
+        let app_state = app_state.clone();
+        Box::pin(async move {
+            match notify_service().retry_failed(batch_size, app_state).await {
+                Ok(0) => {}
+                Ok(sent) => info!("重试发送失败的通知成功 {} 条", sent),
+                Err(err) => error!("重试发送失败的通知失败: {}", err),
+            }
+        })
+    })
+}
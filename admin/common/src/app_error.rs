@@ -4,6 +4,7 @@ use crate::transfer::ResultVO;
 use actix_web::{HttpResponse, ResponseError, http::StatusCode};
 use log::error;
 use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
 
 use super::error_code::{BaseErrorCode, ErrorCode};
 
@@ -14,13 +15,35 @@ pub const NOT_FOUND_CODE: &str = "A000404";
 pub const VALIDATION_ERROR_CODE: &str = "A000400";
 pub const UNAUTHORIZED_CODE: &str = "A000401";
 pub const FORBIDDEN_CODE: &str = "A000403";
+pub const CONFLICT_CODE: &str = "A000409";
 pub const BAD_REQUEST_CODE: &str = "A000400";
+
+/// 校验不通过的字段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// 字段名，与请求 JSON 中的字段名一致
+    pub field: String,
+    /// 错误描述
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
 /// 统一异常类型
 #[derive(Debug, Clone)]
 pub enum AppError {
     Client { code: &'static str, message: String },
     Remote { code: &'static str, message: String },
     Service { code: &'static str, message: String },
+    /// 参数校验错误，携带全部校验不通过的字段
+    Validation { message: String, errors: Vec<FieldError> },
 }
 
 impl AppError {
@@ -68,6 +91,21 @@ impl AppError {
         }
     }
 
+    /// 一次返回多个字段的校验错误
+    pub fn validation_errors(errors: Vec<FieldError>) -> Self {
+        let message = match errors.first() {
+            Some(first) if errors.len() == 1 => format!("{}: {}", first.field, first.message),
+            Some(first) => format!(
+                "{}: {} 等 {} 项参数校验失败",
+                first.field,
+                first.message,
+                errors.len()
+            ),
+            None => "参数校验失败".to_string(),
+        };
+        AppError::Validation { message, errors }
+    }
+
     pub fn not_found(entity: &str, id: impl fmt::Display) -> Self {
         AppError::Client {
             code: NOT_FOUND_CODE,
@@ -89,6 +127,13 @@ impl AppError {
         }
     }
 
+    pub fn conflict(msg: impl ToString) -> Self {
+        AppError::Client {
+            code: CONFLICT_CODE,
+            message: msg.to_string(),
+        }
+    }
+
     pub fn with_context<C: fmt::Display>(self, context: C) -> Self {
         match self {
             AppError::Client { code, message } => AppError::Client {
@@ -103,6 +148,10 @@ impl AppError {
                 code,
                 message: format!("{} | 上下文: {}", message, context),
             },
+            AppError::Validation { message, errors } => AppError::Validation {
+                message: format!("{} | 上下文: {}", message, context),
+                errors,
+            },
         }
     }
 
@@ -111,6 +160,7 @@ impl AppError {
             AppError::Client { code, .. } => code,
             AppError::Remote { code, .. } => code,
             AppError::Service { code, .. } => code,
+            AppError::Validation { .. } => VALIDATION_ERROR_CODE,
         }
     }
 
@@ -119,6 +169,7 @@ impl AppError {
             AppError::Client { message, .. } => message,
             AppError::Remote { message, .. } => message,
             AppError::Service { message, .. } => message,
+            AppError::Validation { message, .. } => message,
         }
     }
 }
@@ -138,8 +189,10 @@ impl ResponseError for AppError {
                 NOT_FOUND_CODE => StatusCode::NOT_FOUND,
                 UNAUTHORIZED_CODE => StatusCode::UNAUTHORIZED,
                 FORBIDDEN_CODE => StatusCode::FORBIDDEN,
+                CONFLICT_CODE => StatusCode::CONFLICT,
                 _ => StatusCode::BAD_REQUEST,
             },
+            AppError::Validation { .. } => StatusCode::BAD_REQUEST,
             AppError::Service { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::Remote { .. } => StatusCode::BAD_GATEWAY,
         }
@@ -151,6 +204,12 @@ impl ResponseError for AppError {
             error!("服务错误: {}", self);
         }
 
+        // 校验错误把全部不通过的字段放在 data 中返回，便于前端逐项提示
+        if let AppError::Validation { errors, .. } = self {
+            return HttpResponse::build(self.status_code())
+                .json(ResultVO::failure_with_data(self, errors.clone()));
+        }
+
         HttpResponse::build(self.status_code()).json(ResultVO::<()>::failure_from_error(self))
     }
 }
@@ -5,6 +5,14 @@ use serde::Deserialize;
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub server: ServerConfig,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub task: TaskConfig,
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -25,6 +33,245 @@ pub struct DatabaseConfig {
     #[serde(default = "default_idle_timeout")]
     pub idle_timeout_seconds: u64,
 }
+#[derive(Debug, Deserialize, Clone)]
+pub struct SchedulerConfig {
+    #[serde(default = "default_scheduler_enabled")]
+    pub enabled: bool,
+    /// 自动结束过期优惠券模板的 cron 表达式 (秒 分 时 日 月 周，UTC)
+    #[serde(default = "default_expire_template_cron")]
+    pub expire_template_cron: String,
+    /// 每个事务中结束的优惠券模板数量
+    #[serde(default = "default_expire_template_batch_size")]
+    pub expire_template_batch_size: u64,
+    /// 检查到达发送时间的分发任务的 cron 表达式 (秒 分 时 日 月 周，UTC)，默认每 10 秒执行一次
+    #[serde(default = "default_dispatch_task_cron")]
+    pub dispatch_task_cron: String,
+    /// 每次最多开始执行的分发任务数量
+    #[serde(default = "default_dispatch_task_batch_size")]
+    pub dispatch_task_batch_size: u64,
+    /// 清理已结束分发任务文件的 cron 表达式 (秒 分 时 日 月 周，UTC)
+    #[serde(default = "default_cleanup_file_cron")]
+    pub cleanup_file_cron: String,
+    /// 每次最多清理文件的分发任务数量
+    #[serde(default = "default_cleanup_file_batch_size")]
+    pub cleanup_file_batch_size: u64,
+    /// 重试发送失败通知的 cron 表达式 (秒 分 时 日 月 周，UTC)，默认每分钟执行一次
+    #[serde(default = "default_retry_notify_cron")]
+    pub retry_notify_cron: String,
+    /// 每次最多重试的通知数量
+    #[serde(default = "default_retry_notify_batch_size")]
+    pub retry_notify_batch_size: u64,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        SchedulerConfig {
+            enabled: default_scheduler_enabled(),
+            expire_template_cron: default_expire_template_cron(),
+            expire_template_batch_size: default_expire_template_batch_size(),
+            dispatch_task_cron: default_dispatch_task_cron(),
+            dispatch_task_batch_size: default_dispatch_task_batch_size(),
+            cleanup_file_cron: default_cleanup_file_cron(),
+            cleanup_file_batch_size: default_cleanup_file_batch_size(),
+            retry_notify_cron: default_retry_notify_cron(),
+            retry_notify_batch_size: default_retry_notify_batch_size(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TaskConfig {
+    /// 分发任务每个事务中处理的用户数量
+    #[serde(default = "default_task_chunk_size")]
+    pub chunk_size: usize,
+}
+
+impl Default for TaskConfig {
+    fn default() -> Self {
+        TaskConfig {
+            chunk_size: default_task_chunk_size(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct NotifyConfig {
+    /// 每条通知最多发送的次数，包括首次发送；失败的通知记录到通知失败记录表，由定时任务重试
+    #[serde(default = "default_notify_max_attempts")]
+    pub max_attempts: u32,
+    /// 首次重试前等待的秒数，之后每次重试翻倍
+    #[serde(default = "default_notify_retry_interval_seconds")]
+    pub retry_interval_seconds: u64,
+    /// 用户每次取走弹框的最大数量，超过时只返回最新的弹框，更早的弹框不再弹出
+    #[serde(default = "default_notify_popup_capacity")]
+    pub popup_capacity: usize,
+    #[serde(default)]
+    pub email: EmailConfig,
+    #[serde(default)]
+    pub sms: SmsConfig,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        NotifyConfig {
+            max_attempts: default_notify_max_attempts(),
+            retry_interval_seconds: default_notify_retry_interval_seconds(),
+            popup_capacity: default_notify_popup_capacity(),
+            email: EmailConfig::default(),
+            sms: SmsConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct EmailConfig {
+    /// SMTP 服务器地址，未配置时不发送邮件通知
+    pub host: Option<String>,
+    #[serde(default = "default_email_port")]
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// 发件人地址
+    #[serde(default = "default_email_from")]
+    pub from: String,
+    /// 是否使用 STARTTLS 加密连接，连接本地测试 SMTP 服务时关闭
+    #[serde(default)]
+    pub starttls: bool,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        EmailConfig {
+            host: None,
+            port: default_email_port(),
+            username: None,
+            password: None,
+            from: default_email_from(),
+            starttls: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SmsConfig {
+    /// 短信服务商发送接口地址，未配置时不发送短信通知
+    pub url: Option<String>,
+    /// 短信服务商接口密钥，以 Bearer 令牌发送
+    pub api_key: Option<String>,
+    #[serde(default = "default_sms_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for SmsConfig {
+    fn default() -> Self {
+        SmsConfig {
+            url: None,
+            api_key: None,
+            timeout_ms: default_sms_timeout_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// 本地文件系统
+    #[default]
+    Local,
+    /// S3 兼容的对象存储，例如 MinIO
+    S3,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StorageConfig {
+    #[serde(default)]
+    pub backend: StorageBackend,
+    #[serde(default)]
+    pub local: LocalStorageConfig,
+    #[serde(default)]
+    pub s3: S3StorageConfig,
+    /// 临时下载链接的有效秒数
+    #[serde(default = "default_storage_link_expire_seconds")]
+    pub link_expire_seconds: u64,
+    /// 已结束的分发任务文件保留天数，超过后由定时任务清理
+    #[serde(default = "default_storage_retention_days")]
+    pub retention_days: i64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            backend: StorageBackend::default(),
+            local: LocalStorageConfig::default(),
+            s3: S3StorageConfig::default(),
+            link_expire_seconds: default_storage_link_expire_seconds(),
+            retention_days: default_storage_retention_days(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LocalStorageConfig {
+    /// 文件保存的根目录
+    #[serde(default = "default_local_storage_root")]
+    pub root: String,
+    /// 临时下载链接的地址前缀，即本服务对外的访问地址
+    #[serde(default = "default_local_storage_base_url")]
+    pub base_url: String,
+    /// 临时下载链接的签名密钥，使用本地存储时必须配置，未配置时启动失败
+    #[serde(default)]
+    pub secret: String,
+}
+
+impl Default for LocalStorageConfig {
+    fn default() -> Self {
+        LocalStorageConfig {
+            root: default_local_storage_root(),
+            base_url: default_local_storage_base_url(),
+            secret: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct S3StorageConfig {
+    /// 服务地址，例如 `http://127.0.0.1:9000`
+    #[serde(default = "default_s3_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    #[serde(default = "default_s3_bucket")]
+    pub bucket: String,
+    #[serde(default)]
+    pub access_key: String,
+    #[serde(default)]
+    pub secret_key: String,
+    /// 是否使用路径形式访问存储桶 (`endpoint/bucket/key`)，MinIO 等自建服务通常需要开启
+    #[serde(default = "default_s3_path_style")]
+    pub path_style: bool,
+    /// 建立连接的超时毫秒数
+    #[serde(default = "default_s3_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+    /// 读取响应的超时毫秒数，两次收到数据的间隔超过该时间时请求失败
+    #[serde(default = "default_s3_read_timeout_ms")]
+    pub read_timeout_ms: u64,
+}
+
+impl Default for S3StorageConfig {
+    fn default() -> Self {
+        S3StorageConfig {
+            endpoint: default_s3_endpoint(),
+            region: default_s3_region(),
+            bucket: default_s3_bucket(),
+            access_key: String::new(),
+            secret_key: String::new(),
+            path_style: default_s3_path_style(),
+            connect_timeout_ms: default_s3_connect_timeout_ms(),
+            read_timeout_ms: default_s3_read_timeout_ms(),
+        }
+    }
+}
+
 const LOG_CONFIG_PATH: &str = "log4rs.yaml";
 const APP_CONFIG_PATH: &str = "admin/application";
 
@@ -65,3 +312,81 @@ fn default_connect_timeout() -> u64 {
 fn default_idle_timeout() -> u64 {
     600
 } // 10 minutes, sqlx 默认
+fn default_scheduler_enabled() -> bool {
+    true
+}
+fn default_expire_template_cron() -> String {
+    "0 * * * * *".into()
+} // 每分钟执行一次
+fn default_expire_template_batch_size() -> u64 {
+    200
+}
+fn default_dispatch_task_cron() -> String {
+    "*/10 * * * * *".into()
+}
+fn default_dispatch_task_batch_size() -> u64 {
+    100
+}
+fn default_cleanup_file_cron() -> String {
+    "0 0 19 * * *".into()
+} // 每天北京时间 3 点执行一次
+fn default_cleanup_file_batch_size() -> u64 {
+    100
+}
+fn default_retry_notify_cron() -> String {
+    "0 * * * * *".into()
+}
+fn default_retry_notify_batch_size() -> u64 {
+    200
+}
+fn default_task_chunk_size() -> usize {
+    500
+}
+fn default_notify_max_attempts() -> u32 {
+    3
+}
+fn default_notify_retry_interval_seconds() -> u64 {
+    60
+}
+fn default_notify_popup_capacity() -> usize {
+    100
+}
+fn default_email_port() -> u16 {
+    25
+}
+fn default_email_from() -> String {
+    "coupon@localhost".into()
+}
+fn default_sms_timeout_ms() -> u64 {
+    5000
+} // 5 seconds
+fn default_storage_link_expire_seconds() -> u64 {
+    600
+} // 10 minutes
+fn default_storage_retention_days() -> i64 {
+    30
+}
+fn default_local_storage_root() -> String {
+    "upload".into()
+}
+fn default_local_storage_base_url() -> String {
+    "http://127.0.0.1:10010".into()
+}
+fn default_s3_endpoint() -> String {
+    "http://127.0.0.1:9000".into()
+}
+fn default_s3_region() -> String {
+    "us-east-1".into()
+}
+fn default_s3_bucket() -> String {
+    "coupon".into()
+}
+fn default_s3_path_style() -> bool {
+    true
+}
+fn default_s3_connect_timeout_ms() -> u64 {
+    5000
+} // 5 seconds
+fn default_s3_read_timeout_ms() -> u64 {
+    30000
+} // 30 seconds
@@ -1,4 +1,4 @@
-use chrono::FixedOffset;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
 
 // 公共常量和辅助函数，供本模块内的子模块和外部使用
 pub const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
@@ -8,10 +8,30 @@ pub fn gmt8_offset() -> FixedOffset {
     FixedOffset::east_opt(GMT8_OFFSET_SECONDS).unwrap()
 }
 
+/// 将 UTC 时间格式化为 GMT+8 时区的 "yyyy-MM-dd HH:mm:ss" 字符串
+pub fn format_gmt8(date_utc: &DateTime<Utc>) -> String {
+    date_utc.with_timezone(&gmt8_offset()).format(FORMAT).to_string()
+}
+
+/// 将 GMT+8 时区的本地时间转换为 UTC 时间，本地时间无效或有歧义时返回 `None`
+pub fn gmt8_to_utc(naive_dt: &NaiveDateTime) -> Option<DateTime<Utc>> {
+    gmt8_offset()
+        .from_local_datetime(naive_dt)
+        .single()
+        .map(|date_gmt8| date_gmt8.with_timezone(&Utc))
+}
+
+/// 解析 GMT+8 时区的 "yyyy-MM-dd HH:mm:ss" 字符串为 UTC 时间
+pub fn parse_gmt8(s: &str) -> Result<DateTime<Utc>, String> {
+    let naive_dt = NaiveDateTime::parse_from_str(s, FORMAT)
+        .map_err(|e| format!("解析日期字符串 '{}' 失败: {}", s, e))?;
+    gmt8_to_utc(&naive_dt).ok_or_else(|| format!("日期时间 '{}' 在 GMT+8 时区无效或有歧义", s))
+}
+
 /// 用于 Option<DateTime<Utc>> 和 GMT+8 字符串 "yyyy-MM-dd HH:mm:ss" 之间的序列化/反序列化
 pub mod serde_option_datetime_utc_as_gmt8_string {
-    use super::{gmt8_offset, FORMAT};
-    use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+    use super::{format_gmt8, parse_gmt8};
+    use chrono::{DateTime, Utc};
     use serde::{self, Deserialize, Deserializer, Serializer};
 
     /// 序列化 Option<DateTime<Utc>> 为 GMT+8 时区的 "yyyy-MM-dd HH:mm:ss" 字符串
@@ -20,11 +40,7 @@ pub mod serde_option_datetime_utc_as_gmt8_string {
         S: Serializer,
     {
         match opt_date_utc {
-            Some(date_utc) => {
-                let date_gmt8 = date_utc.with_timezone(&gmt8_offset());
-                let s = date_gmt8.format(FORMAT).to_string();
-                serializer.serialize_str(&s)
-            }
+            Some(date_utc) => serializer.serialize_str(&format_gmt8(date_utc)),
             None => serializer.serialize_none(),
         }
     }
@@ -36,17 +52,7 @@ pub mod serde_option_datetime_utc_as_gmt8_string {
     {
         let opt_s: Option<String> = Option::deserialize(deserializer)?;
         match opt_s {
-            Some(s) => {
-                let naive_dt = NaiveDateTime::parse_from_str(&s, FORMAT)
-                    .map_err(|e| serde::de::Error::custom(format!("解析日期字符串 '{}' 失败: {}", s, e)))?;
-                match gmt8_offset().from_local_datetime(&naive_dt).single() {
-                    Some(date_gmt8_fixed) => Ok(Some(date_gmt8_fixed.with_timezone(&Utc))),
-                    None => Err(serde::de::Error::custom(format!(
-                        "日期时间 '{}' 在 GMT+8 时区无效或有歧义",
-                        s
-                    ))),
-                }
-            }
+            Some(s) => parse_gmt8(&s).map(Some).map_err(serde::de::Error::custom),
             None => Ok(None),
         }
     }
@@ -105,14 +111,14 @@ mod tests_for_utc_formatter {
     #[test]
     fn deserialize_null_to_none_utc() {
         let json = r#"{"timestamp":null}"#;
-        let deserialized: TestUtcContainer = serde_json::from_str(&json).unwrap();
+        let deserialized: TestUtcContainer = serde_json::from_str(json).unwrap();
         assert_eq!(deserialized.timestamp, None);
     }
 
     #[test]
     fn deserialize_missing_field_to_none_utc() {
         let json = r#"{}"#;
-        let deserialized: TestUtcContainer = serde_json::from_str(&json).unwrap();
+        let deserialized: TestUtcContainer = serde_json::from_str(json).unwrap();
         assert_eq!(deserialized.timestamp, None);
     }
 
@@ -34,6 +34,10 @@ pub enum BaseErrorCode {
     // 添加一个参数无效的错误码
     InvalidParam,
 
+    // 二级宏观错误码 优惠券领取错误
+    CouponStockExhausted,
+    CouponReceiveLimitExceeded,
+
     // 一级宏观错误码 系统执行出错
     ServiceError,
     // 二级宏观错误码 系统执行超时
@@ -59,6 +63,8 @@ impl ErrorCode for BaseErrorCode {
             BaseErrorCode::IdempotentTokenDeleteError => "A000201",
             BaseErrorCode::SearchAmountExceedsLimit => "A000300",
             BaseErrorCode::InvalidParam => "A000400",
+            BaseErrorCode::CouponStockExhausted => "A000501",
+            BaseErrorCode::CouponReceiveLimitExceeded => "A000502",
             BaseErrorCode::ServiceError => "B000001",
             BaseErrorCode::ServiceTimeoutError => "B000100",
             BaseErrorCode::RemoteError => "C000001",
@@ -80,6 +86,8 @@ impl ErrorCode for BaseErrorCode {
             BaseErrorCode::IdempotentTokenDeleteError => "幂等Token已被使用或失效",
             BaseErrorCode::SearchAmountExceedsLimit => "查询数据量超过最大限制",
             BaseErrorCode::InvalidParam => "无效的请求参数",
+            BaseErrorCode::CouponStockExhausted => "优惠券已被领完",
+            BaseErrorCode::CouponReceiveLimitExceeded => "已达到每人限领张数",
             BaseErrorCode::ServiceError => "系统执行出错",
             BaseErrorCode::ServiceTimeoutError => "系统执行超时",
             BaseErrorCode::RemoteError => "调用第三方服务出错",
@@ -0,0 +1,35 @@
+use chrono::Utc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// 时间戳的起始时间 2024-01-01 00:00:00 UTC
+const EPOCH_MILLIS: i64 = 1_704_067_200_000;
+/// 序列号占用的位数，同一毫秒内最多生成 2^22 个 ID
+const SEQUENCE_BITS: u32 = 22;
+
+static LAST_ID: AtomicI64 = AtomicI64::new(0);
+
+/// 生成进程内单调递增的 ID，用于分发任务批次ID等业务编号
+///
+/// 高位为自 2024 年起的毫秒数，低位为序列号；同一毫秒内依次递增，时钟回拨时沿用上一个 ID 继续递增。
+/// 多个实例同时生成时不保证全局唯一，需要唯一性的场景由数据库约束兜底
+pub fn next_id() -> i64 {
+    let timestamp_id = (Utc::now().timestamp_millis() - EPOCH_MILLIS) << SEQUENCE_BITS;
+    let previous = LAST_ID
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            Some(timestamp_id.max(last + 1))
+        })
+        .unwrap_or_else(|last| last);
+    timestamp_id.max(previous + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_strictly_increasing() {
+        let ids: Vec<i64> = (0..1000).map(|_| next_id()).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(ids[0] > 0);
+    }
+}
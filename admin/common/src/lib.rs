@@ -4,3 +4,5 @@ pub mod error_code;
 pub mod transfer;
 pub mod config;
 
+pub mod id_generator;
+pub mod storage;
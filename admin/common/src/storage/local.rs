@@ -0,0 +1,216 @@
+use crate::app_error::AppError;
+use crate::config::LocalStorageConfig;
+use crate::storage::{Storage, check_key};
+use actix_web::web;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::error;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use sea_orm::prelude::async_trait::async_trait;
+use sha2::Sha256;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 本地存储临时下载链接的路径，由 api 校验签名后返回文件内容
+pub const DOWNLOAD_PATH: &str = "/api/file/download";
+
+/// 本地文件系统存储，文件保存在根目录下与文件键相同的相对路径中
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: String,
+    secret: String,
+}
+
+impl LocalStorage {
+    /// 未配置签名密钥时仍可以保存和读取文件，只是不能生成临时下载链接
+    pub fn new(config: &LocalStorageConfig) -> Self {
+        LocalStorage {
+            root: PathBuf::from(&config.root),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            secret: config.secret.clone(),
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, AppError> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+impl fmt::Debug for LocalStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalStorage")
+            .field("root", &self.root)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn upload(&self, key: &str, content: Vec<u8>) -> Result<String, AppError> {
+        let path = self.path(key)?;
+        web::block(move || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, content)
+        })
+        .await
+        .map_err(AppError::internal_error)?
+        .map_err(|err| {
+            error!("保存文件失败: {}, 错误: {}", key, err);
+            AppError::internal_error(format!("保存文件失败: {}", err))
+        })?;
+        Ok(key.to_string())
+    }
+
+    async fn download(&self, address: &str) -> Result<Vec<u8>, AppError> {
+        let path = self.path(address)?;
+        web::block(move || std::fs::read(&path))
+            .await
+            .map_err(AppError::internal_error)?
+            .map_err(|err| match err.kind() {
+                ErrorKind::NotFound => AppError::not_found("文件", address),
+                _ => AppError::internal_error(format!("读取文件失败: {}", err)),
+            })
+    }
+
+    /// 链接指向本服务的下载接口，携带过期时间和以密钥计算的签名
+    ///
+    /// 未配置签名密钥时返回错误，不会生成可以被伪造的链接
+    fn signed_url(&self, address: &str, expires_in: Duration) -> Result<String, AppError> {
+        check_key(address)?;
+        if self.secret.is_empty() {
+            return Err(AppError::internal_error("未配置临时下载链接的签名密钥"));
+        }
+        let expires = Utc::now().timestamp() + expires_in.as_secs() as i64;
+        Ok(format!(
+            "{}{}?key={}&expires={}&signature={}",
+            self.base_url,
+            DOWNLOAD_PATH,
+            utf8_percent_encode(address, NON_ALPHANUMERIC),
+            expires,
+            sign(&self.secret, address, expires)
+        ))
+    }
+
+    async fn delete(&self, address: &str) -> Result<(), AppError> {
+        let path = self.path(address)?;
+        web::block(move || std::fs::remove_file(&path))
+            .await
+            .map_err(AppError::internal_error)?
+            .or_else(|err| match err.kind() {
+                ErrorKind::NotFound => Ok(()),
+                _ => Err(AppError::internal_error(format!("删除文件失败: {}", err))),
+            })
+    }
+}
+
+/// 校验临时下载链接的签名和过期时间 (Unix 秒)
+pub fn verify(secret: &str, key: &str, expires: i64, signature: &str) -> bool {
+    if secret.is_empty() || expires < Utc::now().timestamp() {
+        return false;
+    }
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    mac(secret, key, expires).verify_slice(&signature).is_ok()
+}
+
+fn sign(secret: &str, key: &str, expires: i64) -> String {
+    hex::encode(mac(secret, key, expires).finalize().into_bytes())
+}
+
+fn mac(secret: &str, key: &str, expires: i64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 可以使用任意长度的密钥");
+    mac.update(format!("{}\n{}", key, expires).as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id_generator::next_id;
+
+    #[actix_web::test]
+    async fn stores_files_under_root() {
+        let root = std::env::temp_dir().join(format!("coupon-storage-{}", next_id()));
+        let storage = LocalStorage::new(&LocalStorageConfig {
+            root: root.to_string_lossy().into_owned(),
+            base_url: "http://127.0.0.1:8080/".to_string(),
+            secret: "secret".to_string(),
+        });
+
+        let address = storage
+            .upload("coupon-task/1.csv", b"1001\n".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(address, "coupon-task/1.csv");
+        assert!(root.join("coupon-task/1.csv").is_file());
+        assert_eq!(storage.download(&address).await.unwrap(), b"1001\n");
+
+        storage.delete(&address).await.unwrap();
+        storage.delete(&address).await.unwrap();
+        assert!(storage.download(&address).await.is_err());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn signed_url_expires_and_binds_key() {
+        let storage = LocalStorage::new(&LocalStorageConfig {
+            root: "upload".to_string(),
+            base_url: "http://127.0.0.1:8080/".to_string(),
+            secret: "secret".to_string(),
+        });
+        let url = storage
+            .signed_url("coupon-task/1-fail.csv", Duration::from_secs(60))
+            .unwrap();
+        assert!(url.starts_with(
+            "http://127.0.0.1:8080/api/file/download?key=coupon%2Dtask%2F1%2Dfail%2Ecsv&expires="
+        ));
+
+        let expires = Utc::now().timestamp() + 60;
+        let signature = sign("secret", "coupon-task/1-fail.csv", expires);
+        assert!(verify(
+            "secret",
+            "coupon-task/1-fail.csv",
+            expires,
+            &signature
+        ));
+        assert!(!verify(
+            "secret",
+            "coupon-task/2-fail.csv",
+            expires,
+            &signature
+        ));
+        assert!(!verify(
+            "other",
+            "coupon-task/1-fail.csv",
+            expires,
+            &signature
+        ));
+
+        let expired = Utc::now().timestamp() - 1;
+        let signature = sign("secret", "coupon-task/1-fail.csv", expired);
+        assert!(!verify(
+            "secret",
+            "coupon-task/1-fail.csv",
+            expired,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn signed_url_requires_secret() {
+        let storage = LocalStorage::new(&LocalStorageConfig::default());
+        assert!(
+            storage
+                .signed_url("coupon-task/1-fail.csv", Duration::from_secs(60))
+                .is_err()
+        );
+    }
+}
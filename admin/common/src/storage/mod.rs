@@ -0,0 +1,73 @@
+pub mod local;
+pub mod s3;
+
+use crate::app_error::AppError;
+use crate::config::{StorageBackend, StorageConfig};
+use local::LocalStorage;
+use s3::S3Storage;
+use sea_orm::prelude::async_trait::async_trait;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 文件存储
+///
+/// 文件以文件键标识，例如 `coupon-task/1.csv`，保存到数据库中的文件地址即为文件键，
+/// 与文件实际保存在本地目录还是对象存储无关
+#[async_trait]
+pub trait Storage: Send + Sync + Debug {
+    /// 保存文件，已存在时覆盖，返回文件地址
+    async fn upload(&self, key: &str, content: Vec<u8>) -> Result<String, AppError>;
+
+    /// 读取文件内容，文件不存在时返回未找到
+    async fn download(&self, address: &str) -> Result<Vec<u8>, AppError>;
+
+    /// 生成在 `expires_in` 内有效的临时下载链接，不需要登录即可下载
+    fn signed_url(&self, address: &str, expires_in: Duration) -> Result<String, AppError>;
+
+    /// 删除文件，文件不存在时视为成功
+    async fn delete(&self, address: &str) -> Result<(), AppError>;
+}
+
+/// 根据配置创建文件存储
+pub fn build(config: &StorageConfig) -> Result<Arc<dyn Storage>, AppError> {
+    match config.backend {
+        StorageBackend::Local => Ok(Arc::new(LocalStorage::new(&config.local))),
+        StorageBackend::S3 => Ok(Arc::new(S3Storage::new(&config.s3)?)),
+    }
+}
+
+/// 校验文件键只包含相对路径且不含 `.` 或 `..`，避免访问存储位置之外的文件
+pub(crate) fn check_key(key: &str) -> Result<(), AppError> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && !key.contains('\\')
+        && key
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::validation_error(format!("文件地址无效: {}", key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_keys_outside_storage() {
+        assert!(check_key("coupon-task/1.csv").is_ok());
+        for key in [
+            "",
+            "/etc/passwd",
+            "../1.csv",
+            "coupon-task/../../1.csv",
+            "a//b",
+            "a\\b",
+        ] {
+            assert!(check_key(key).is_err(), "{}", key);
+        }
+    }
+}
@@ -81,6 +81,16 @@ where
         }
     }
 
+    /// 创建失败返回结果，同时携带错误详情数据
+    pub fn failure_with_data(error: &AppError, data: T) -> ResultVO<T> {
+        ResultVO {
+            code: error.code().to_string(),
+            message: error.message().to_string(),
+            data: Some(data),
+            request_id: None,
+        }
+    }
+
     /// 创建失败返回结果，使用指定的错误代码和消息
     pub fn failure_with_code_and_message(code: &str, message: &str) -> ResultVO<T> {
         ResultVO {
@@ -136,3 +146,48 @@ impl<T: Serialize + 'static> Responder for ResultVO<T> {
         }
     }
 }
+
+/// 分页查询结果
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageResult<T>
+where
+    T: Serialize,
+{
+    /// 当前页码，从 1 开始
+    pub current: u64,
+    /// 每页记录数
+    pub size: u64,
+    /// 总记录数
+    pub total: u64,
+    /// 当前页数据
+    pub records: Vec<T>,
+}
+
+impl<T> PageResult<T>
+where
+    T: Serialize,
+{
+    pub fn new(current: u64, size: u64, total: u64, records: Vec<T>) -> PageResult<T> {
+        PageResult {
+            current,
+            size,
+            total,
+            records,
+        }
+    }
+
+    /// 将当前页数据转换为另一种类型，分页信息保持不变
+    pub fn map<U, F>(self, f: F) -> PageResult<U>
+    where
+        U: Serialize,
+        F: FnMut(T) -> U,
+    {
+        PageResult {
+            current: self.current,
+            size: self.size,
+            total: self.total,
+            records: self.records.into_iter().map(f).collect(),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::entity::coupon_notify_fail::{ActiveModel, Column, Entity, Model};
+use crate::enums::NotifyFailStatus;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::sea_query::{Expr, LockBehavior, LockType};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+
+#[async_trait]
+pub trait CouponNotifyFailDao: Send + Sync {
+    /// 在事务中写入一条通知失败记录
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr>;
+
+    /// 查询并锁定已到重试时间的等待重试记录，最多返回 `limit` 条
+    ///
+    /// 已被其他事务锁定的行会被跳过，多个实例同时执行时不会重复重试同一条记录
+    async fn lock_due(
+        &self,
+        txn: &DatabaseTransaction,
+        now: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 将记录的下次重试时间推迟到 `next_retry_time`，返回受影响的行数
+    async fn postpone(
+        &self,
+        txn: &DatabaseTransaction,
+        ids: &[i64],
+        next_retry_time: DateTime<Utc>,
+    ) -> Result<u64, DbErr>;
+
+    /// 记录一次重试失败，返回受影响的行数
+    ///
+    /// `next_retry_time` 为空时记录修改为已放弃
+    async fn record_attempt(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        attempts: i32,
+        reason: String,
+        next_retry_time: Option<DateTime<Utc>>,
+    ) -> Result<u64, DbErr>;
+
+    /// 重试成功后删除记录，返回受影响的行数
+    async fn delete(&self, txn: &DatabaseTransaction, id: i64) -> Result<u64, DbErr>;
+}
+
+/// 优惠券发放通知失败记录数据访问对象实现
+pub struct CouponNotifyFailDaoImpl;
+
+#[async_trait]
+impl CouponNotifyFailDao for CouponNotifyFailDaoImpl {
+    /// 在事务中写入一条通知失败记录
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr> {
+        let mut active_model: ActiveModel = model.clone().into();
+        active_model.id = ActiveValue::NotSet;
+
+        active_model.insert(txn).await
+    }
+
+    /// 查询并锁定已到重试时间的等待重试记录
+    ///
+    /// 使用 `SELECT ... FOR UPDATE SKIP LOCKED`，按下次重试时间正序返回
+    async fn lock_due(
+        &self,
+        txn: &DatabaseTransaction,
+        now: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Status.eq(NotifyFailStatus::Retrying))
+            .filter(Column::NextRetryTime.lte(now))
+            .order_by_asc(Column::NextRetryTime)
+            .limit(limit)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(txn)
+            .await
+    }
+
+    /// 推迟记录的下次重试时间
+    async fn postpone(
+        &self,
+        txn: &DatabaseTransaction,
+        ids: &[i64],
+        next_retry_time: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let result = Entity::update_many()
+            .col_expr(Column::NextRetryTime, Expr::value(next_retry_time))
+            .col_expr(Column::UpdateTime, Expr::value(Utc::now()))
+            .filter(Column::Id.is_in(ids.iter().copied()))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 记录一次重试失败
+    async fn record_attempt(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        attempts: i32,
+        reason: String,
+        next_retry_time: Option<DateTime<Utc>>,
+    ) -> Result<u64, DbErr> {
+        let status = match next_retry_time {
+            Some(_) => NotifyFailStatus::Retrying,
+            None => NotifyFailStatus::Abandoned,
+        };
+        let result = Entity::update_many()
+            .col_expr(Column::Attempts, Expr::value(attempts))
+            .col_expr(Column::Reason, Expr::value(reason))
+            .col_expr(Column::Status, Expr::value(status))
+            .col_expr(Column::NextRetryTime, Expr::value(next_retry_time))
+            .col_expr(Column::UpdateTime, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 重试成功后删除记录
+    async fn delete(&self, txn: &DatabaseTransaction, id: i64) -> Result<u64, DbErr> {
+        let result = Entity::delete_by_id(id).exec(txn).await?;
+        Ok(result.rows_affected)
+    }
+}
+
+static COUPON_NOTIFY_FAIL_DAO: Lazy<CouponNotifyFailDaoImpl> =
+    Lazy::new(|| CouponNotifyFailDaoImpl);
+
+pub fn coupon_notify_fail_dao() -> &'static dyn CouponNotifyFailDao {
+    &*COUPON_NOTIFY_FAIL_DAO
+}
@@ -0,0 +1,51 @@
+use crate::dao::user_coupon::CouponScope;
+use crate::entity::coupon_settlement::{Column, Entity, Relation};
+use crate::entity::user_coupon;
+use crate::enums::SettlementStatus;
+use crate::soft_delete::SoftDelete;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::prelude::Decimal;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, JoinType, QueryFilter, QuerySelect,
+    RelationTrait,
+};
+
+#[async_trait]
+pub trait CouponSettlementDao: Send + Sync {
+    /// 统计范围内已支付结算单的优惠金额合计
+    async fn sum_paid_discount(
+        &self,
+        db: &DatabaseConnection,
+        scope: CouponScope,
+    ) -> Result<Decimal, DbErr>;
+}
+
+/// 优惠券结算单数据访问对象实现
+pub struct CouponSettlementDaoImpl;
+
+#[async_trait]
+impl CouponSettlementDao for CouponSettlementDaoImpl {
+    /// 统计已支付结算单的优惠金额合计，已取消和已退款的结算单不计入
+    async fn sum_paid_discount(
+        &self,
+        db: &DatabaseConnection,
+        scope: CouponScope,
+    ) -> Result<Decimal, DbErr> {
+        let query = Entity::find()
+            .select_only()
+            .column_as(Column::DiscountAmount.sum(), "total")
+            .join(JoinType::InnerJoin, Relation::UserCoupon.def())
+            .filter(Column::Status.eq(SettlementStatus::Paid))
+            .filter(user_coupon::Entity::alive());
+
+        let total: Option<Option<Decimal>> = scope.apply(query).into_tuple().one(db).await?;
+        Ok(total.flatten().unwrap_or_default())
+    }
+}
+
+static COUPON_SETTLEMENT_DAO: Lazy<CouponSettlementDaoImpl> = Lazy::new(|| CouponSettlementDaoImpl);
+
+pub fn coupon_settlement_dao() -> &'static dyn CouponSettlementDao {
+    &*COUPON_SETTLEMENT_DAO
+}
@@ -0,0 +1,382 @@
+use crate::entity::coupon_task::{ActiveModel, Column, Entity, Model};
+use crate::enums::TaskStatus;
+use crate::soft_delete::SoftDelete;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::sea_query::{Expr, LockBehavior, LockType};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction,
+    DbErr, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+
+/// 分发任务分页查询条件
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    /// 店铺编号，查询总是限定在该店铺内
+    pub shop_number: i64,
+    /// 任务名称，模糊匹配
+    pub task_name: Option<String>,
+    /// 优惠券模板ID
+    pub coupon_template_id: Option<i64>,
+    /// 任务状态
+    pub status: Option<TaskStatus>,
+}
+
+impl TaskFilter {
+    fn to_condition(&self) -> Condition {
+        let mut condition = Condition::all().add(Column::ShopNumber.eq(self.shop_number));
+
+        if let Some(task_name) = self.task_name.as_deref().filter(|s| !s.trim().is_empty()) {
+            condition = condition.add(Column::TaskName.contains(task_name.trim()));
+        }
+        if let Some(coupon_template_id) = self.coupon_template_id {
+            condition = condition.add(Column::CouponTemplateId.eq(coupon_template_id));
+        }
+        if let Some(status) = &self.status {
+            condition = condition.add(Column::Status.eq(status.clone()));
+        }
+        condition
+    }
+}
+
+#[async_trait]
+pub trait CouponTaskDao: Send + Sync {
+    /// 在事务中创建分发任务
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr>;
+
+    /// 分页查询分发任务，页码从 1 开始，返回当前页数据和总记录数
+    async fn page(
+        &self,
+        db: &DatabaseConnection,
+        filter: &TaskFilter,
+        page: u64,
+        size: u64,
+    ) -> Result<(Vec<Model>, u64), DbErr>;
+
+    /// 根据 ID 查询未删除的分发任务
+    async fn find_by_id(&self, db: &DatabaseConnection, id: i64) -> Result<Option<Model>, DbErr>;
+
+    /// 在事务中根据 ID 查询未删除的分发任务，并对该行加排他锁
+    async fn find_by_id_for_update(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+    ) -> Result<Option<Model>, DbErr>;
+
+    /// 查询指定状态的全部未删除分发任务
+    async fn list_by_status(
+        &self,
+        db: &DatabaseConnection,
+        status: TaskStatus,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 查询并锁定已到发送时间的待执行任务，最多返回 `limit` 条
+    ///
+    /// 已被其他事务锁定的行会被跳过，多个实例同时执行时不会重复开始同一个任务
+    async fn lock_due(
+        &self,
+        txn: &DatabaseTransaction,
+        now: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 修改待执行任务的发送时间，返回受影响的行数
+    async fn reschedule(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        send_time: DateTime<Utc>,
+    ) -> Result<u64, DbErr>;
+
+    /// 将执行失败的任务修改为执行中并记录需要重试的失败记录，返回受影响的行数
+    async fn start_retry(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        retry_fail_id: Option<i64>,
+    ) -> Result<u64, DbErr>;
+
+    /// 将任务状态从 `from` 修改为 `to`，返回受影响的行数
+    ///
+    /// 修改为终止状态时同时记录完成时间
+    async fn transit(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        from: TaskStatus,
+        to: TaskStatus,
+    ) -> Result<u64, DbErr>;
+
+    /// 修改任务的发放失败用户文件地址，返回受影响的行数
+    async fn update_fail_file_address(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        fail_file_address: Option<String>,
+    ) -> Result<u64, DbErr>;
+
+    /// 查询完成时间早于 `before` 且用户文件未清理或有失败用户文件的已结束任务，最多返回 `limit` 条
+    async fn list_files_to_clean(
+        &self,
+        db: &DatabaseConnection,
+        before: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 标记任务的用户文件已清理并清空失败用户文件地址，返回受影响的行数
+    async fn mark_file_cleaned(&self, txn: &DatabaseTransaction, id: i64) -> Result<u64, DbErr>;
+
+    /// 累加执行中任务的已处理、成功和失败数量，返回受影响的行数
+    async fn advance(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        processed: i32,
+        success: i32,
+        fail: i32,
+    ) -> Result<u64, DbErr>;
+}
+
+/// 分发任务数据访问对象实现
+pub struct CouponTaskDaoImpl;
+
+#[async_trait]
+impl CouponTaskDao for CouponTaskDaoImpl {
+    /// 在事务中创建分发任务，ID 由数据库生成
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr> {
+        let mut active_model: ActiveModel = model.clone().into();
+        active_model.id = ActiveValue::NotSet;
+
+        active_model.insert(txn).await
+    }
+
+    /// 分页查询分发任务，按 ID 倒序排列
+    async fn page(
+        &self,
+        db: &DatabaseConnection,
+        filter: &TaskFilter,
+        page: u64,
+        size: u64,
+    ) -> Result<(Vec<Model>, u64), DbErr> {
+        let paginator = Entity::find_alive()
+            .filter(filter.to_condition())
+            .order_by_desc(Column::Id)
+            .paginate(db, size);
+
+        let total = paginator.num_items().await?;
+        let records = paginator.fetch_page(page.saturating_sub(1)).await?;
+        Ok((records, total))
+    }
+
+    /// 根据 ID 查询未删除的分发任务
+    async fn find_by_id(&self, db: &DatabaseConnection, id: i64) -> Result<Option<Model>, DbErr> {
+        Entity::find_alive_by_id(id).one(db).await
+    }
+
+    /// 在事务中根据 ID 查询未删除的分发任务，并对该行加排他锁
+    async fn find_by_id_for_update(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find_alive_by_id(id).lock_exclusive().one(txn).await
+    }
+
+    /// 查询指定状态的全部未删除分发任务，按 ID 正序排列
+    async fn list_by_status(
+        &self,
+        db: &DatabaseConnection,
+        status: TaskStatus,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find_alive()
+            .filter(Column::Status.eq(status))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// 查询并锁定已到发送时间的待执行任务
+    ///
+    /// 使用 `SELECT ... FOR UPDATE SKIP LOCKED`，按发送时间正序返回
+    async fn lock_due(
+        &self,
+        txn: &DatabaseTransaction,
+        now: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find_alive()
+            .filter(Column::Status.eq(TaskStatus::Pending))
+            .filter(Column::SendTime.lte(now))
+            .order_by_asc(Column::SendTime)
+            .limit(limit)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(txn)
+            .await
+    }
+
+    /// 修改待执行任务的发送时间
+    async fn reschedule(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        send_time: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(Column::SendTime, Expr::value(send_time))
+            .col_expr(Column::UpdateTime, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.eq(TaskStatus::Pending))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 将执行失败的任务修改为执行中
+    ///
+    /// 只更新执行失败状态的任务，重复提交时只有一次生效。同时清空完成时间和失败用户文件地址，
+    /// 重试结束后重新生成失败用户文件
+    async fn start_retry(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        retry_fail_id: Option<i64>,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(Column::Status, Expr::value(TaskStatus::Running))
+            .col_expr(Column::RetryFailId, Expr::value(retry_fail_id))
+            .col_expr(Column::FailFileAddress, Expr::value(Option::<String>::None))
+            .col_expr(
+                Column::CompletionTime,
+                Expr::value(Option::<DateTime<Utc>>::None),
+            )
+            .col_expr(Column::UpdateTime, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.eq(TaskStatus::Failed))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 修改任务状态
+    ///
+    /// 只有当前状态仍为 `from` 时才会修改，多个执行者同时处理同一任务时只有一个能成功
+    async fn transit(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        from: TaskStatus,
+        to: TaskStatus,
+    ) -> Result<u64, DbErr> {
+        let now = Utc::now();
+        let mut update = Entity::update_alive()
+            .col_expr(Column::UpdateTime, Expr::value(now))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.eq(from));
+        if to.is_terminal() {
+            update = update.col_expr(Column::CompletionTime, Expr::value(now));
+        }
+
+        let result = update
+            .col_expr(Column::Status, Expr::value(to))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 修改任务的发放失败用户文件地址
+    async fn update_fail_file_address(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        fail_file_address: Option<String>,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(Column::FailFileAddress, Expr::value(fail_file_address))
+            .col_expr(Column::UpdateTime, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 查询文件需要清理的已结束任务，按完成时间正序排列
+    async fn list_files_to_clean(
+        &self,
+        db: &DatabaseConnection,
+        before: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find_alive()
+            .filter(
+                Condition::any()
+                    .add(Column::FileCleaned.eq(0))
+                    .add(Column::FailFileAddress.is_not_null()),
+            )
+            .filter(Column::CompletionTime.lt(before))
+            .filter(Column::Status.is_in([
+                TaskStatus::Failed,
+                TaskStatus::Succeeded,
+                TaskStatus::Canceled,
+            ]))
+            .order_by_asc(Column::CompletionTime)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// 标记任务的用户文件已清理
+    ///
+    /// 只更新已结束的任务，清理期间被重试的任务不会被标记。失败用户文件可以由失败记录重新生成，
+    /// 下载时重新生成的文件在之后的清理中再次删除
+    async fn mark_file_cleaned(&self, txn: &DatabaseTransaction, id: i64) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(Column::FileCleaned, Expr::value(1))
+            .col_expr(Column::FailFileAddress, Expr::value(Option::<String>::None))
+            .col_expr(Column::UpdateTime, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.is_in([
+                TaskStatus::Failed,
+                TaskStatus::Succeeded,
+                TaskStatus::Canceled,
+            ]))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 累加执行中任务的已处理、成功和失败数量
+    ///
+    /// 使用 `SET processed_num = processed_num + ?`，与发放结果在同一事务中提交
+    async fn advance(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+        processed: i32,
+        success: i32,
+        fail: i32,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(
+                Column::ProcessedNum,
+                Expr::col(Column::ProcessedNum).add(processed),
+            )
+            .col_expr(
+                Column::SuccessNum,
+                Expr::col(Column::SuccessNum).add(success),
+            )
+            .col_expr(Column::FailNum, Expr::col(Column::FailNum).add(fail))
+            .col_expr(Column::UpdateTime, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.eq(TaskStatus::Running))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
+
+static COUPON_TASK_DAO: Lazy<CouponTaskDaoImpl> = Lazy::new(|| CouponTaskDaoImpl);
+
+pub fn coupon_task_dao() -> &'static dyn CouponTaskDao {
+    &*COUPON_TASK_DAO
+}
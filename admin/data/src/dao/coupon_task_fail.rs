@@ -0,0 +1,122 @@
+use crate::entity::coupon_task_fail::{ActiveModel, Column, Entity, Model};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+
+#[async_trait]
+pub trait CouponTaskFailDao: Send + Sync {
+    /// 在事务中批量写入发放失败记录
+    async fn create_batch(&self, txn: &DatabaseTransaction, models: &[Model]) -> Result<(), DbErr>;
+
+    /// 按 ID 正序分批查询批次的发放失败记录，返回 ID 大于 `after_id` 的最多 `limit` 条记录
+    async fn list_after(
+        &self,
+        db: &DatabaseConnection,
+        batch_id: i64,
+        after_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 查询批次最后一条发放失败记录的 ID，没有记录时返回 `None`
+    async fn max_id(&self, txn: &DatabaseTransaction, batch_id: i64) -> Result<Option<i64>, DbErr>;
+
+    /// 在事务中按 ID 正序查询批次中 ID 不大于 `max_id` 的最多 `limit` 条发放失败记录
+    async fn list_until(
+        &self,
+        txn: &DatabaseTransaction,
+        batch_id: i64,
+        max_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 在事务中删除发放失败记录
+    async fn delete_by_ids(&self, txn: &DatabaseTransaction, ids: &[i64]) -> Result<u64, DbErr>;
+}
+
+/// 分发任务发放失败记录数据访问对象实现
+pub struct CouponTaskFailDaoImpl;
+
+#[async_trait]
+impl CouponTaskFailDao for CouponTaskFailDaoImpl {
+    /// 在事务中批量写入发放失败记录，使用单条多行 `INSERT`
+    async fn create_batch(&self, txn: &DatabaseTransaction, models: &[Model]) -> Result<(), DbErr> {
+        if models.is_empty() {
+            return Ok(());
+        }
+        let active_models = models.iter().map(|model| {
+            let mut active_model: ActiveModel = model.clone().into();
+            active_model.id = ActiveValue::NotSet;
+            active_model
+        });
+
+        Entity::insert_many(active_models).exec(txn).await?;
+        Ok(())
+    }
+
+    /// 按 ID 正序分批查询批次的发放失败记录，以上一批最后一条记录的 ID 作为游标
+    async fn list_after(
+        &self,
+        db: &DatabaseConnection,
+        batch_id: i64,
+        after_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::BatchId.eq(batch_id))
+            .filter(Column::Id.gt(after_id))
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// 查询批次最后一条发放失败记录的 ID
+    async fn max_id(&self, txn: &DatabaseTransaction, batch_id: i64) -> Result<Option<i64>, DbErr> {
+        Entity::find()
+            .select_only()
+            .column_as(Column::Id.max(), "max_id")
+            .filter(Column::BatchId.eq(batch_id))
+            .into_tuple::<Option<i64>>()
+            .one(txn)
+            .await
+            .map(Option::flatten)
+    }
+
+    /// 按 ID 正序查询批次中 ID 不大于 `max_id` 的发放失败记录
+    async fn list_until(
+        &self,
+        txn: &DatabaseTransaction,
+        batch_id: i64,
+        max_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::BatchId.eq(batch_id))
+            .filter(Column::Id.lte(max_id))
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .all(txn)
+            .await
+    }
+
+    /// 在事务中删除发放失败记录
+    async fn delete_by_ids(&self, txn: &DatabaseTransaction, ids: &[i64]) -> Result<u64, DbErr> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let result = Entity::delete_many()
+            .filter(Column::Id.is_in(ids.iter().copied()))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
+
+static COUPON_TASK_FAIL_DAO: Lazy<CouponTaskFailDaoImpl> = Lazy::new(|| CouponTaskFailDaoImpl);
+
+pub fn coupon_task_fail_dao() -> &'static dyn CouponTaskFailDao {
+    &*COUPON_TASK_FAIL_DAO
+}
@@ -1 +1,10 @@
+pub mod coupon_notify_fail;
+pub mod coupon_settlement;
+pub mod coupon_task;
+pub mod coupon_task_fail;
 pub mod template;
+pub mod template_log;
+pub mod user_contact;
+pub mod user_coupon;
+pub mod user_message;
+pub mod user_popup;
@@ -1,14 +1,173 @@
-use crate::entity::template::{ActiveModel, Model};
+use crate::entity::template::{ActiveModel, Column, Entity, Model};
+use crate::enums::{AuditStatus, CouponStatus, CouponTarget, CouponType};
+use crate::soft_delete::{SoftDelete, DELETED, NOT_DELETED};
+use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
 use sea_orm::prelude::async_trait::async_trait;
 // 修改：使用sync版本
-use sea_orm::{ActiveModelTrait, DatabaseConnection, DbErr};
+use sea_orm::sea_query::{Expr, LockBehavior, LockType};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction,
+    DbErr, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+
+/// 优惠券模板分页查询条件
+#[derive(Debug, Clone, Default)]
+pub struct TemplateFilter {
+    /// 店铺编号，查询总是限定在该店铺内
+    pub shop_number: i64,
+    /// 优惠券名称，模糊匹配
+    pub name: Option<String>,
+    /// 优惠类型
+    pub r#type: Option<CouponType>,
+    /// 优惠对象
+    pub target: Option<CouponTarget>,
+    /// 优惠券状态
+    pub status: Option<CouponStatus>,
+    /// 审核状态
+    pub audit_status: Option<AuditStatus>,
+    /// 优惠商品编码
+    pub goods: Option<String>,
+    /// 有效期开始时间下限
+    pub valid_start_time: Option<DateTime<Utc>>,
+    /// 有效期结束时间上限
+    pub valid_end_time: Option<DateTime<Utc>>,
+}
+
+impl TemplateFilter {
+    fn to_condition(&self) -> Condition {
+        let mut condition = Condition::all().add(Column::ShopNumber.eq(self.shop_number));
+
+        if let Some(name) = self.name.as_deref().filter(|s| !s.trim().is_empty()) {
+            condition = condition.add(Column::Name.contains(name.trim()));
+        }
+        if let Some(r#type) = &self.r#type {
+            condition = condition.add(Column::Type.eq(r#type.clone()));
+        }
+        if let Some(target) = &self.target {
+            condition = condition.add(Column::Target.eq(target.clone()));
+        }
+        if let Some(status) = &self.status {
+            condition = condition.add(Column::Status.eq(status.clone()));
+        }
+        if let Some(audit_status) = &self.audit_status {
+            condition = condition.add(Column::AuditStatus.eq(audit_status.clone()));
+        }
+        if let Some(goods) = self.goods.as_deref().filter(|s| !s.trim().is_empty()) {
+            condition = condition.add(Column::Goods.eq(goods.trim()));
+        }
+        if let Some(start) = self.valid_start_time {
+            condition = condition.add(Column::ValidStartTime.gte(start));
+        }
+        if let Some(end) = self.valid_end_time {
+            condition = condition.add(Column::ValidEndTime.lte(end));
+        }
+        condition
+    }
+}
 
 // 定义 TemplateDao 特征，添加async_trait
 #[async_trait]
 pub trait TemplateDao: Send + Sync {
-    /// 创建新的优惠券模板
-    async fn create(&self, db: &DatabaseConnection, model: &Model) -> Result<Model, DbErr>;
+    /// 在事务中创建新的优惠券模板
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr>;
+
+    /// 分页查询优惠券模板，页码从 1 开始，返回当前页数据和总记录数
+    async fn page(
+        &self,
+        db: &DatabaseConnection,
+        filter: &TemplateFilter,
+        page: u64,
+        size: u64,
+    ) -> Result<(Vec<Model>, u64), DbErr>;
+
+    /// 按 ID 倒序分批查询优惠券模板，返回 ID 小于 `before_id` 的最多 `limit` 条记录
+    ///
+    /// 用于导出等需要遍历全部结果的场景，`before_id` 为空时从最新的记录开始
+    async fn list_before(
+        &self,
+        db: &DatabaseConnection,
+        filter: &TemplateFilter,
+        before_id: Option<i64>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 根据 ID 查询未删除的优惠券模板
+    async fn find_by_id(&self, db: &DatabaseConnection, id: i64) -> Result<Option<Model>, DbErr>;
+
+    /// 统计店铺未删除的优惠券模板数量
+    async fn count_by_shop(&self, db: &DatabaseConnection, shop_number: i64) -> Result<u64, DbErr>;
+
+    /// 在事务中根据 ID 查询未删除的优惠券模板，并对该行加排他锁
+    async fn find_by_id_for_update(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+    ) -> Result<Option<Model>, DbErr>;
+
+    /// 为生效中的优惠券模板增加库存，返回受影响的行数
+    ///
+    /// `template` 为事务中锁定的模板；增加后库存会超出 `i32::MAX` 时不做修改
+    async fn increase_stock(
+        &self,
+        txn: &DatabaseTransaction,
+        template: &Model,
+        number: i32,
+    ) -> Result<u64, DbErr>;
+
+    /// 扣减生效中的优惠券模板的库存，返回受影响的行数
+    ///
+    /// `template` 为事务中锁定的模板；库存不足 `number` 时不做修改
+    async fn decrease_stock(
+        &self,
+        txn: &DatabaseTransaction,
+        template: &Model,
+        number: i32,
+    ) -> Result<u64, DbErr>;
+
+    /// 将事务中锁定的生效中优惠券模板修改为已结束，返回受影响的行数
+    async fn terminate(&self, txn: &DatabaseTransaction, template: &Model) -> Result<u64, DbErr>;
+
+    /// 在事务中根据 ID 查询优惠券模板并加排他锁，包括已删除的模板
+    async fn find_by_id_with_deleted_for_update(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+    ) -> Result<Option<Model>, DbErr>;
+
+    /// 查询并锁定已过有效期但仍生效中的优惠券模板，最多返回 `limit` 条
+    ///
+    /// 已被其他事务锁定的行会被跳过，多个实例同时执行时不会互相等待或重复处理
+    async fn lock_expired(
+        &self,
+        txn: &DatabaseTransaction,
+        now: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 将审核状态从 `from` 修改为模型中的审核信息，返回受影响的行数
+    async fn update_audit(
+        &self,
+        txn: &DatabaseTransaction,
+        model: &Model,
+        from: AuditStatus,
+    ) -> Result<u64, DbErr>;
+
+    /// 逻辑删除事务中锁定的优惠券模板，返回受影响的行数
+    async fn soft_delete(&self, txn: &DatabaseTransaction, template: &Model) -> Result<u64, DbErr>;
+
+    /// 恢复事务中锁定的已逻辑删除的优惠券模板，返回受影响的行数
+    async fn restore(&self, txn: &DatabaseTransaction, template: &Model) -> Result<u64, DbErr>;
+
+    /// 修改优惠券模板的可编辑字段，返回受影响的行数
+    ///
+    /// 只有数据库中的修改时间仍等于 `expected_update_time` 时才会更新，用于乐观并发控制
+    async fn update_with_version(
+        &self,
+        txn: &DatabaseTransaction,
+        model: &Model,
+        expected_update_time: Option<DateTime<Utc>>,
+    ) -> Result<u64, DbErr>;
 }
 
 /// 优惠券模板数据访问对象实现
@@ -17,11 +176,245 @@ pub struct TemplateDaoImpl;
 // 实现 TemplateDao 特征，添加async_trait
 #[async_trait]
 impl TemplateDao for TemplateDaoImpl {
-    /// 创建新的优惠券模板
-    async fn create(&self, db: &DatabaseConnection, model: &Model) -> Result<Model, DbErr> {
-        let active_model: ActiveModel = model.clone().into();
+    /// 在事务中创建新的优惠券模板，ID 由数据库生成
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr> {
+        let mut active_model: ActiveModel = model.clone().into();
+        active_model.id = ActiveValue::NotSet;
+
+        active_model.insert(txn).await
+    }
+
+    /// 分页查询优惠券模板，按 ID 倒序排列
+    async fn page(
+        &self,
+        db: &DatabaseConnection,
+        filter: &TemplateFilter,
+        page: u64,
+        size: u64,
+    ) -> Result<(Vec<Model>, u64), DbErr> {
+        let paginator = Entity::find_alive()
+            .filter(filter.to_condition())
+            .order_by_desc(Column::Id)
+            .paginate(db, size);
 
-        active_model.insert(db).await
+        let total = paginator.num_items().await?;
+        let records = paginator.fetch_page(page.saturating_sub(1)).await?;
+        Ok((records, total))
+    }
+
+    /// 按 ID 倒序分批查询优惠券模板
+    ///
+    /// 以上一批最后一条记录的 ID 作为游标，避免深分页时 `OFFSET` 的性能问题
+    async fn list_before(
+        &self,
+        db: &DatabaseConnection,
+        filter: &TemplateFilter,
+        before_id: Option<i64>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        let mut query = Entity::find_alive().filter(filter.to_condition());
+        if let Some(before_id) = before_id {
+            query = query.filter(Column::Id.lt(before_id));
+        }
+        query.order_by_desc(Column::Id).limit(limit).all(db).await
+    }
+
+    /// 根据 ID 查询未删除的优惠券模板
+    async fn find_by_id(&self, db: &DatabaseConnection, id: i64) -> Result<Option<Model>, DbErr> {
+        Entity::find_alive_by_id(id).one(db).await
+    }
+
+    /// 统计店铺未删除的优惠券模板数量
+    async fn count_by_shop(&self, db: &DatabaseConnection, shop_number: i64) -> Result<u64, DbErr> {
+        Entity::find_alive()
+            .filter(Column::ShopNumber.eq(shop_number))
+            .count(db)
+            .await
+    }
+
+    /// 在事务中根据 ID 查询未删除的优惠券模板，并对该行加排他锁
+    async fn find_by_id_for_update(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find_alive_by_id(id).lock_exclusive().one(txn).await
+    }
+
+    /// 为生效中的优惠券模板增加库存
+    ///
+    /// 使用单条带条件的 `UPDATE ... SET stock = stock + ?`，不依赖先读后写
+    async fn increase_stock(
+        &self,
+        txn: &DatabaseTransaction,
+        template: &Model,
+        number: i32,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(Column::Stock, Expr::col(Column::Stock).add(number))
+            .col_expr(Column::UpdateTime, Expr::value(template.next_version()))
+            .filter(Column::Id.eq(template.id))
+            .filter(Column::ShopNumber.eq(template.shop_number))
+            .filter(Column::Status.eq(CouponStatus::Active))
+            .filter(Column::Stock.lte(i32::MAX - number))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 扣减生效中的优惠券模板的库存
+    ///
+    /// 使用单条带条件的 `UPDATE ... SET stock = stock - ? WHERE stock >= ?`，不会扣成负数
+    async fn decrease_stock(
+        &self,
+        txn: &DatabaseTransaction,
+        template: &Model,
+        number: i32,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(Column::Stock, Expr::col(Column::Stock).sub(number))
+            .col_expr(Column::UpdateTime, Expr::value(template.next_version()))
+            .filter(Column::Id.eq(template.id))
+            .filter(Column::Status.eq(CouponStatus::Active))
+            .filter(Column::Stock.gte(number))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 将生效中的优惠券模板修改为已结束
+    async fn terminate(&self, txn: &DatabaseTransaction, template: &Model) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(Column::Status, Expr::value(CouponStatus::Ended))
+            .col_expr(Column::UpdateTime, Expr::value(template.next_version()))
+            .filter(Column::Id.eq(template.id))
+            .filter(Column::Status.eq(CouponStatus::Active))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 在事务中根据 ID 查询优惠券模板并加排他锁，包括已删除的模板
+    async fn find_by_id_with_deleted_for_update(
+        &self,
+        txn: &DatabaseTransaction,
+        id: i64,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find_with_deleted()
+            .filter(Column::Id.eq(id))
+            .lock_exclusive()
+            .one(txn)
+            .await
+    }
+
+    /// 查询并锁定已过有效期但仍生效中的优惠券模板
+    ///
+    /// 使用 `SELECT ... FOR UPDATE SKIP LOCKED`，按 ID 正序返回
+    async fn lock_expired(
+        &self,
+        txn: &DatabaseTransaction,
+        now: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find_alive()
+            .filter(Column::Status.eq(CouponStatus::Active))
+            .filter(Column::ValidEndTime.lt(now))
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(txn)
+            .await
+    }
+
+    /// 修改审核状态、审核人、审核意见、审核时间和修改时间
+    ///
+    /// 只有当前审核状态仍为 `from` 时才会修改，避免重复审核；修改时间使用 `model` 中的新版本号
+    async fn update_audit(
+        &self,
+        txn: &DatabaseTransaction,
+        model: &Model,
+        from: AuditStatus,
+    ) -> Result<u64, DbErr> {
+        let active_model = ActiveModel {
+            audit_status: ActiveValue::Set(model.audit_status.clone()),
+            reviewer_id: ActiveValue::Set(model.reviewer_id),
+            audit_remark: ActiveValue::Set(model.audit_remark.clone()),
+            audit_time: ActiveValue::Set(model.audit_time),
+            update_time: ActiveValue::Set(model.update_time),
+            ..Default::default()
+        };
+
+        let result = Entity::update_alive()
+            .set(active_model)
+            .filter(Column::Id.eq(model.id))
+            .filter(Column::AuditStatus.eq(from))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 逻辑删除优惠券模板，同时记录删除时间
+    async fn soft_delete(&self, txn: &DatabaseTransaction, template: &Model) -> Result<u64, DbErr> {
+        let result = Entity::update_alive()
+            .col_expr(Column::DelFlag, Expr::value(DELETED))
+            .col_expr(Column::DeleteTime, Expr::value(Utc::now()))
+            .col_expr(Column::UpdateTime, Expr::value(template.next_version()))
+            .filter(Column::Id.eq(template.id))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 恢复已逻辑删除的优惠券模板
+    async fn restore(&self, txn: &DatabaseTransaction, template: &Model) -> Result<u64, DbErr> {
+        let result = Entity::update_deleted()
+            .col_expr(Column::DelFlag, Expr::value(NOT_DELETED))
+            .col_expr(
+                Column::DeleteTime,
+                Expr::value(Option::<DateTime<Utc>>::None),
+            )
+            .col_expr(Column::UpdateTime, Expr::value(template.next_version()))
+            .filter(Column::Id.eq(template.id))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 修改优惠券模板的可编辑字段和审核信息，库存、来源、状态等字段不受影响
+    async fn update_with_version(
+        &self,
+        txn: &DatabaseTransaction,
+        model: &Model,
+        expected_update_time: Option<DateTime<Utc>>,
+    ) -> Result<u64, DbErr> {
+        let active_model = ActiveModel {
+            name: ActiveValue::Set(model.name.clone()),
+            target: ActiveValue::Set(model.target.clone()),
+            goods: ActiveValue::Set(model.goods.clone()),
+            r#type: ActiveValue::Set(model.r#type.clone()),
+            valid_start_time: ActiveValue::Set(model.valid_start_time),
+            valid_end_time: ActiveValue::Set(model.valid_end_time),
+            receive_rule: ActiveValue::Set(model.receive_rule.clone()),
+            consume_rule: ActiveValue::Set(model.consume_rule.clone()),
+            audit_status: ActiveValue::Set(model.audit_status.clone()),
+            reviewer_id: ActiveValue::Set(model.reviewer_id),
+            audit_remark: ActiveValue::Set(model.audit_remark.clone()),
+            audit_time: ActiveValue::Set(model.audit_time),
+            update_time: ActiveValue::Set(model.update_time),
+            ..Default::default()
+        };
+        let version = match expected_update_time {
+            Some(update_time) => Column::UpdateTime.eq(update_time),
+            None => Column::UpdateTime.is_null(),
+        };
+
+        let result = Entity::update_alive()
+            .set(active_model)
+            .filter(Column::Id.eq(model.id))
+            .filter(version)
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
     }
 }
 
@@ -32,3 +425,68 @@ static TEMPLATE_DAO: Lazy<TemplateDaoImpl> = Lazy::new(|| TemplateDaoImpl);
 pub fn template_dao() -> &'static dyn TemplateDao {
     &*TEMPLATE_DAO
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DatabaseBackend, Iden, Iterable, MockDatabase, Transaction, Value};
+    use std::collections::BTreeMap;
+
+    /// 查询模板全部字段的 SQL
+    fn select_columns() -> String {
+        let columns: Vec<String> = Column::iter()
+            .map(|column| format!("`t_coupon_template`.`{}`", column.to_string()))
+            .collect();
+        format!("SELECT {} FROM `t_coupon_template`", columns.join(", "))
+    }
+
+    #[actix_web::test]
+    async fn page_and_find_by_id_exclude_deleted_templates() {
+        let db = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results([[BTreeMap::from([("num_items", Value::Int(Some(0)))])]])
+            .append_query_results([Vec::<Model>::new(), Vec::<Model>::new()])
+            .into_connection();
+        let filter = TemplateFilter {
+            shop_number: 1,
+            ..Default::default()
+        };
+
+        let dao = template_dao();
+        assert_eq!(dao.page(&db, &filter, 1, 10).await.unwrap(), (vec![], 0));
+        assert_eq!(dao.find_by_id(&db, 1).await.unwrap(), None);
+
+        let page_condition =
+            "WHERE `t_coupon_template`.`del_flag` = ? AND `t_coupon_template`.`shop_number` = ?";
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![
+                Transaction::from_sql_and_values(
+                    DatabaseBackend::MySql,
+                    format!(
+                        "SELECT COUNT(*) AS num_items FROM ({} {}) AS `sub_query`",
+                        select_columns(),
+                        page_condition
+                    ),
+                    [NOT_DELETED.into(), 1i64.into()],
+                ),
+                Transaction::from_sql_and_values(
+                    DatabaseBackend::MySql,
+                    format!(
+                        "{} {} ORDER BY `t_coupon_template`.`id` DESC LIMIT ? OFFSET ?",
+                        select_columns(),
+                        page_condition
+                    ),
+                    [NOT_DELETED.into(), 1i64.into(), 10u64.into(), 0u64.into()],
+                ),
+                Transaction::from_sql_and_values(
+                    DatabaseBackend::MySql,
+                    format!(
+                        "{} WHERE `t_coupon_template`.`id` = ? AND `t_coupon_template`.`del_flag` = ? LIMIT ?",
+                        select_columns()
+                    ),
+                    [1i64.into(), NOT_DELETED.into(), 1u64.into()],
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use crate::entity::template_log::{ActiveModel, Column, Entity, Model};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DatabaseTransaction, DbErr,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+};
+
+#[async_trait]
+pub trait TemplateLogDao: Send + Sync {
+    /// 在事务中写入一条优惠券模板操作日志
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr>;
+
+    /// 分页查询指定模板的操作日志，页码从 1 开始，返回当前页数据和总记录数
+    async fn page_by_template(
+        &self,
+        db: &DatabaseConnection,
+        shop_number: i64,
+        coupon_template_id: i64,
+        page: u64,
+        size: u64,
+    ) -> Result<(Vec<Model>, u64), DbErr>;
+}
+
+/// 优惠券模板操作日志数据访问对象实现
+pub struct TemplateLogDaoImpl;
+
+#[async_trait]
+impl TemplateLogDao for TemplateLogDaoImpl {
+    /// 在事务中写入一条优惠券模板操作日志
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr> {
+        let mut active_model: ActiveModel = model.clone().into();
+        active_model.id = ActiveValue::NotSet;
+
+        active_model.insert(txn).await
+    }
+
+    /// 分页查询指定模板的操作日志，按时间倒序排列
+    async fn page_by_template(
+        &self,
+        db: &DatabaseConnection,
+        shop_number: i64,
+        coupon_template_id: i64,
+        page: u64,
+        size: u64,
+    ) -> Result<(Vec<Model>, u64), DbErr> {
+        let paginator = Entity::find()
+            .filter(Column::ShopNumber.eq(shop_number))
+            .filter(Column::CouponTemplateId.eq(coupon_template_id))
+            .order_by_desc(Column::Id)
+            .paginate(db, size);
+
+        let total = paginator.num_items().await?;
+        let records = paginator.fetch_page(page.saturating_sub(1)).await?;
+        Ok((records, total))
+    }
+}
+
+static TEMPLATE_LOG_DAO: Lazy<TemplateLogDaoImpl> = Lazy::new(|| TemplateLogDaoImpl);
+
+pub fn template_log_dao() -> &'static dyn TemplateLogDao {
+    &*TEMPLATE_LOG_DAO
+}
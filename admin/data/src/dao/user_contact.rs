@@ -0,0 +1,35 @@
+use crate::entity::user_contact::{Entity, Model};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+
+#[async_trait]
+pub trait UserContactDao: Send + Sync {
+    /// 查询用户的联系方式
+    async fn find_by_user_id(
+        &self,
+        db: &DatabaseConnection,
+        user_id: i64,
+    ) -> Result<Option<Model>, DbErr>;
+}
+
+/// 用户联系方式数据访问对象实现
+pub struct UserContactDaoImpl;
+
+#[async_trait]
+impl UserContactDao for UserContactDaoImpl {
+    /// 查询用户的联系方式
+    async fn find_by_user_id(
+        &self,
+        db: &DatabaseConnection,
+        user_id: i64,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find_by_id(user_id).one(db).await
+    }
+}
+
+static USER_CONTACT_DAO: Lazy<UserContactDaoImpl> = Lazy::new(|| UserContactDaoImpl);
+
+pub fn user_contact_dao() -> &'static dyn UserContactDao {
+    &*USER_CONTACT_DAO
+}
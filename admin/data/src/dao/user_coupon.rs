@@ -0,0 +1,149 @@
+use crate::entity::template;
+use crate::entity::user_coupon::{ActiveModel, Column, Entity, Model, Relation};
+use crate::enums::UserCouponStatus;
+use crate::soft_delete::SoftDelete;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DatabaseTransaction, DbErr,
+    EntityTrait, JoinType, PaginatorTrait, QueryFilter, QuerySelect, RelationTrait, Select,
+};
+
+/// 用户优惠券统计范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CouponScope {
+    /// 指定优惠券模板发出的优惠券
+    Template(i64),
+    /// 指定店铺全部未删除模板发出的优惠券
+    Shop(i64),
+}
+
+impl CouponScope {
+    /// 为以用户优惠券为主表的查询加上统计范围条件
+    pub(crate) fn apply<E: sea_orm::EntityTrait>(&self, query: Select<E>) -> Select<E> {
+        match *self {
+            CouponScope::Template(id) => query.filter(Column::CouponTemplateId.eq(id)),
+            CouponScope::Shop(shop_number) => query
+                .join(JoinType::InnerJoin, Relation::Template.def())
+                .filter(template::Column::ShopNumber.eq(shop_number))
+                .filter(template::Entity::alive()),
+        }
+    }
+}
+
+#[async_trait]
+pub trait UserCouponDao: Send + Sync {
+    /// 统计指定模板已发出的用户优惠券数量
+    async fn count_by_template(
+        &self,
+        txn: &DatabaseTransaction,
+        coupon_template_id: i64,
+    ) -> Result<u64, DbErr>;
+
+    /// 按状态分组统计范围内未删除的用户优惠券数量
+    async fn count_by_status(
+        &self,
+        db: &DatabaseConnection,
+        scope: CouponScope,
+    ) -> Result<Vec<(UserCouponStatus, i64)>, DbErr>;
+
+    /// 查询用户在指定模板下已有的最大领取次数，返回 `(用户ID, 最大领取次数)`，没有领取过的用户不返回
+    async fn max_receive_counts(
+        &self,
+        txn: &DatabaseTransaction,
+        coupon_template_id: i64,
+        user_ids: &[i64],
+    ) -> Result<Vec<(i64, i32)>, DbErr>;
+
+    /// 在事务中批量写入用户优惠券，ID 由数据库生成
+    async fn create_batch(&self, txn: &DatabaseTransaction, models: &[Model]) -> Result<(), DbErr>;
+
+    /// 在事务中写入一张用户优惠券，ID 由数据库生成
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr>;
+}
+
+/// 用户优惠券数据访问对象实现
+pub struct UserCouponDaoImpl;
+
+#[async_trait]
+impl UserCouponDao for UserCouponDaoImpl {
+    /// 统计指定模板已发出的用户优惠券数量
+    async fn count_by_template(
+        &self,
+        txn: &DatabaseTransaction,
+        coupon_template_id: i64,
+    ) -> Result<u64, DbErr> {
+        Entity::find_alive()
+            .filter(Column::CouponTemplateId.eq(coupon_template_id))
+            .count(txn)
+            .await
+    }
+
+    /// 按状态分组统计用户优惠券数量，使用 `GROUP BY status` 在数据库中聚合
+    async fn count_by_status(
+        &self,
+        db: &DatabaseConnection,
+        scope: CouponScope,
+    ) -> Result<Vec<(UserCouponStatus, i64)>, DbErr> {
+        let query = Entity::find_alive()
+            .select_only()
+            .column(Column::Status)
+            .column_as(Column::Id.count(), "count")
+            .group_by(Column::Status);
+
+        scope.apply(query).into_tuple().all(db).await
+    }
+
+    /// 查询用户在指定模板下已有的最大领取次数
+    ///
+    /// 包括已删除的用户优惠券，保证新的领取次数不会与唯一索引冲突
+    async fn max_receive_counts(
+        &self,
+        txn: &DatabaseTransaction,
+        coupon_template_id: i64,
+        user_ids: &[i64],
+    ) -> Result<Vec<(i64, i32)>, DbErr> {
+        if user_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Entity::find_with_deleted()
+            .select_only()
+            .column(Column::UserId)
+            .column_as(Column::ReceiveCount.max(), "receive_count")
+            .filter(Column::CouponTemplateId.eq(coupon_template_id))
+            .filter(Column::UserId.is_in(user_ids.iter().copied()))
+            .group_by(Column::UserId)
+            .into_tuple()
+            .all(txn)
+            .await
+    }
+
+    /// 在事务中批量写入用户优惠券，使用单条多行 `INSERT`
+    async fn create_batch(&self, txn: &DatabaseTransaction, models: &[Model]) -> Result<(), DbErr> {
+        if models.is_empty() {
+            return Ok(());
+        }
+        let active_models = models.iter().map(|model| {
+            let mut active_model: ActiveModel = model.clone().into();
+            active_model.id = ActiveValue::NotSet;
+            active_model
+        });
+
+        Entity::insert_many(active_models).exec(txn).await?;
+        Ok(())
+    }
+
+    /// 在事务中写入一张用户优惠券，返回写入后的数据
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr> {
+        let mut active_model: ActiveModel = model.clone().into();
+        active_model.id = ActiveValue::NotSet;
+
+        active_model.insert(txn).await
+    }
+}
+
+static USER_COUPON_DAO: Lazy<UserCouponDaoImpl> = Lazy::new(|| UserCouponDaoImpl);
+
+pub fn user_coupon_dao() -> &'static dyn UserCouponDao {
+    &*USER_COUPON_DAO
+}
@@ -0,0 +1,30 @@
+use crate::entity::user_message::{ActiveModel, Model};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseTransaction, DbErr};
+
+#[async_trait]
+pub trait UserMessageDao: Send + Sync {
+    /// 在事务中写入一条站内信
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr>;
+}
+
+/// 用户站内信数据访问对象实现
+pub struct UserMessageDaoImpl;
+
+#[async_trait]
+impl UserMessageDao for UserMessageDaoImpl {
+    /// 在事务中写入一条站内信
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr> {
+        let mut active_model: ActiveModel = model.clone().into();
+        active_model.id = ActiveValue::NotSet;
+
+        active_model.insert(txn).await
+    }
+}
+
+static USER_MESSAGE_DAO: Lazy<UserMessageDaoImpl> = Lazy::new(|| UserMessageDaoImpl);
+
+pub fn user_message_dao() -> &'static dyn UserMessageDao {
+    &*USER_MESSAGE_DAO
+}
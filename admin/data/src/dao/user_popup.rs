@@ -0,0 +1,84 @@
+use crate::entity::user_popup::{ActiveModel, Column, Entity, Model};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+
+#[async_trait]
+pub trait UserPopupDao: Send + Sync {
+    /// 在事务中写入一条弹框消息
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr>;
+
+    /// 查询用户最新的未弹出消息并加排他锁，最多返回 `limit` 条
+    async fn list_pending_for_update(
+        &self,
+        txn: &DatabaseTransaction,
+        user_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// 将用户 ID 不大于 `max_id` 的未弹出消息标记为已弹出，返回受影响的行数
+    async fn mark_popped(
+        &self,
+        txn: &DatabaseTransaction,
+        user_id: i64,
+        max_id: i64,
+    ) -> Result<u64, DbErr>;
+}
+
+/// 用户弹框消息数据访问对象实现
+pub struct UserPopupDaoImpl;
+
+#[async_trait]
+impl UserPopupDao for UserPopupDaoImpl {
+    /// 在事务中写入一条弹框消息
+    async fn create(&self, txn: &DatabaseTransaction, model: &Model) -> Result<Model, DbErr> {
+        let mut active_model: ActiveModel = model.clone().into();
+        active_model.id = ActiveValue::NotSet;
+
+        active_model.insert(txn).await
+    }
+
+    /// 查询用户最新的未弹出消息，按 ID 倒序排列
+    async fn list_pending_for_update(
+        &self,
+        txn: &DatabaseTransaction,
+        user_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::PoppedFlag.eq(0))
+            .order_by_desc(Column::Id)
+            .limit(limit)
+            .lock_exclusive()
+            .all(txn)
+            .await
+    }
+
+    /// 标记弹框消息已弹出，包括超过数量上限未返回的更早消息
+    async fn mark_popped(
+        &self,
+        txn: &DatabaseTransaction,
+        user_id: i64,
+        max_id: i64,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::PoppedFlag, Expr::value(1))
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::PoppedFlag.eq(0))
+            .filter(Column::Id.lte(max_id))
+            .exec(txn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
+
+static USER_POPUP_DAO: Lazy<UserPopupDaoImpl> = Lazy::new(|| UserPopupDaoImpl);
+
+pub fn user_popup_dao() -> &'static dyn UserPopupDao {
+    &*USER_POPUP_DAO
+}
@@ -0,0 +1,57 @@
+use crate::enums::{NotifyFailStatus, NotifyType};
+use chrono::{DateTime, Utc};
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
+use serde::{Deserialize, Serialize};
+
+/// 优惠券发放通知失败记录数据对象
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "t_coupon_notify_fail")]
+pub struct Model {
+    /// 失败记录ID，主键
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 批次ID，对应分发任务的批次ID
+    pub batch_id: i64,
+
+    /// 用户ID
+    pub user_id: i64,
+
+    /// 通知方式
+    pub notify_type: NotifyType,
+
+    /// 通知标题，重试时原样发送
+    pub title: String,
+
+    /// 通知内容，重试时原样发送
+    pub content: String,
+
+    /// 已尝试发送的次数
+    pub attempts: i32,
+
+    /// 最后一次失败的原因
+    pub reason: String,
+
+    /// 状态
+    pub status: NotifyFailStatus,
+
+    /// 下次重试时间，已放弃时为空 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub next_retry_time: Option<DateTime<Utc>>,
+
+    /// 创建时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub create_time: Option<DateTime<Utc>>,
+
+    /// 更新时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub update_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,52 @@
+use crate::enums::SettlementStatus;
+use chrono::{DateTime, Utc};
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
+use serde::{Deserialize, Serialize};
+
+/// 优惠券结算单数据对象
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "t_coupon_settlement")]
+pub struct Model {
+    /// 结算单ID，主键
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 订单ID
+    pub order_id: i64,
+
+    /// 用户ID
+    pub user_id: i64,
+
+    /// 用户优惠券ID
+    pub coupon_id: i64,
+
+    /// 结算单状态
+    pub status: SettlementStatus,
+
+    /// 优惠金额
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub discount_amount: Option<Decimal>,
+
+    /// 创建时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub create_time: Option<DateTime<Utc>>,
+
+    /// 更新时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub update_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user_coupon::Entity",
+        from = "Column::CouponId",
+        to = "super::user_coupon::Column::Id"
+    )]
+    UserCoupon,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,95 @@
+use crate::enums::{TaskSendType, TaskStatus};
+use crate::soft_delete::SoftDelete;
+use chrono::{DateTime, Utc};
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
+use serde::{Deserialize, Serialize};
+
+/// 优惠券分发任务数据对象
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "t_coupon_task")]
+pub struct Model {
+    /// 任务ID，主键
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 商店编号
+    pub shop_number: i64,
+
+    /// 批次ID，关联发放失败记录
+    pub batch_id: i64,
+
+    /// 任务名称
+    pub task_name: String,
+
+    /// 用户文件地址
+    pub file_address: String,
+
+    /// 发放失败用户文件地址
+    pub fail_file_address: Option<String>,
+
+    /// 用户文件是否已清理 0：未清理 1：已清理，超过保留期限后由定时任务删除
+    pub file_cleaned: i32,
+
+    /// 发放数量，即用户文件中的用户数
+    pub send_num: i32,
+
+    /// 已处理用户数量，任务按用户文件中的顺序分批处理，重启后从这里继续
+    pub processed_num: i32,
+
+    /// 发放成功数量
+    pub success_num: i32,
+
+    /// 发放失败数量，失败的用户记录在发放失败记录表中
+    pub fail_num: i32,
+
+    /// 重试时需要重新处理的最后一条发放失败记录ID，ID 不大于它的失败记录会被重新处理
+    pub retry_fail_id: Option<i64>,
+
+    /// 通知方式，多个以逗号分隔 0：站内信 1：弹框推送 2：邮箱 3：短信
+    pub notify_type: Option<String>,
+
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+
+    /// 发送类型
+    pub send_type: TaskSendType,
+
+    /// 发送时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub send_time: Option<DateTime<Utc>>,
+
+    /// 任务状态
+    pub status: TaskStatus,
+
+    /// 完成时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub completion_time: Option<DateTime<Utc>>,
+
+    /// 创建时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub create_time: Option<DateTime<Utc>>,
+
+    /// 操作人
+    pub operator_id: Option<i64>,
+
+    /// 更新时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub update_time: Option<DateTime<Utc>>,
+
+    /// 删除标志
+    pub del_flag: i32,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl SoftDelete for Entity {
+    fn del_flag() -> Column {
+        Column::DelFlag
+    }
+}
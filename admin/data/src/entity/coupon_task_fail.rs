@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
+use serde::{Deserialize, Serialize};
+
+/// 优惠券分发任务发放失败记录数据对象
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "t_coupon_task_fail")]
+pub struct Model {
+    /// 失败记录ID，主键
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 批次ID，对应分发任务的批次ID
+    pub batch_id: i64,
+
+    /// 失败内容 (JSON 字符串，包含用户ID和失败原因)
+    #[sea_orm(column_type = "Text")]
+    pub json_object: String,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,6 @@
-use crate::enums::{CouponSource, CouponStatus, CouponTarget, CouponType};
-use chrono::{DateTime, Utc};
+use crate::enums::{AuditStatus, CouponSource, CouponStatus, CouponTarget, CouponType};
+use crate::soft_delete::{SoftDelete, NOT_DELETED};
+use chrono::{DateTime, Duration, SubsecRound, Utc};
 use common::datetime::serde_option_datetime_utc_as_gmt8_string;
 use sea_orm::entity::prelude::*;
 use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
@@ -47,11 +48,11 @@ pub struct Model {
 
     /// 领取规则
     #[sea_orm(column_type = "JsonBinary")]
-    pub receive_rule: Option<JsonValue>, // 保持 Option 因为 DTO->Model 转换时 .ok() 可能产生 None
+    pub receive_rule: Option<JsonValue>, // 保持 Option 因为历史数据中该列可能为 NULL
 
     /// 消费规则
     #[sea_orm(column_type = "JsonBinary")]
-    pub consume_rule: Option<JsonValue>, // 保持 Option 因为 DTO->Model 转换时 .ok() 可能产生 None
+    pub consume_rule: Option<JsonValue>, // 保持 Option 因为历史数据中该列可能为 NULL
 
     /// 优惠券状态
     pub status: CouponStatus,
@@ -66,6 +67,23 @@ pub struct Model {
 
     /// 删除标志
     pub del_flag: i32,
+
+    /// 删除时间，用于判断是否超过恢复的保留期 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub delete_time: Option<DateTime<Utc>>,
+
+    /// 审核状态
+    pub audit_status: AuditStatus,
+
+    /// 审核人ID
+    pub reviewer_id: Option<i64>,
+
+    /// 审核意见，驳回时为驳回原因
+    pub audit_remark: Option<String>,
+
+    /// 审核时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub audit_time: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
@@ -73,3 +91,53 @@ pub enum Relation {}
 
 // 保持简单的行为定义
 impl ActiveModelBehavior for ActiveModel {}
+
+impl SoftDelete for Entity {
+    fn del_flag() -> Column {
+        Column::DelFlag
+    }
+}
+
+impl Model {
+    /// 模板是否仍在生效中
+    ///
+    /// 已结束或已删除的模板不能再被领取、发放或追加库存
+    pub fn is_active(&self) -> bool {
+        self.status == CouponStatus::Active && self.del_flag == NOT_DELETED
+    }
+
+    /// 模板是否可以被领取或发放：生效中且审核通过
+    pub fn is_issuable(&self) -> bool {
+        self.is_active() && self.audit_status == AuditStatus::Approved
+    }
+
+    /// 模板修改后的版本号，即新的修改时间
+    ///
+    /// 修改时间同时作为乐观并发控制的版本号，所有修改模板的操作都用它设置修改时间。
+    /// 数据库只保存到秒，新的版本号至少比旧版本晚一秒，保证同一秒内的两次修改也能区分
+    pub fn next_version(&self) -> Option<DateTime<Utc>> {
+        next_version(self.update_time)
+    }
+}
+
+fn next_version(update_time: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    let now = Utc::now().trunc_subsecs(0);
+    Some(update_time.map_or(now, |old| now.max(old + Duration::seconds(1))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_version_is_later_than_previous() {
+        let previous = Utc::now().trunc_subsecs(0) + Duration::seconds(5);
+        assert_eq!(
+            next_version(Some(previous)),
+            Some(previous + Duration::seconds(1))
+        );
+
+        let version = next_version(None).unwrap();
+        assert_eq!(version, version.trunc_subsecs(0));
+    }
+}
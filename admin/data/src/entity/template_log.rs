@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
+use serde::{Deserialize, Serialize};
+
+/// 优惠券模板操作日志数据对象
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "t_coupon_template_log")]
+pub struct Model {
+    /// 日志ID，主键
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 商店编号
+    pub shop_number: i64,
+
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+
+    /// 操作人
+    pub operator_id: Option<i64>,
+
+    /// 操作日志
+    #[sea_orm(column_type = "Text")]
+    pub operation_log: String,
+
+    /// 原始数据 (JSON 字符串，仅包含发生变化的字段)
+    #[sea_orm(column_type = "Text")]
+    pub original_data: Option<String>,
+
+    /// 修改后数据 (JSON 字符串，仅包含发生变化的字段)
+    #[sea_orm(column_type = "Text")]
+    pub modified_data: Option<String>,
+
+    /// 创建时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub create_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
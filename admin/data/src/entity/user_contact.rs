@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
+use serde::{Deserialize, Serialize};
+
+/// 用户联系方式数据对象，由用户服务同步，用于发送邮件和短信通知
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "t_user_contact")]
+pub struct Model {
+    /// 用户ID，主键
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: i64,
+
+    /// 手机号
+    pub phone: Option<String>,
+
+    /// 邮箱
+    pub email: Option<String>,
+
+    /// 更新时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub update_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,77 @@
+use crate::enums::{UserCouponSource, UserCouponStatus};
+use crate::soft_delete::SoftDelete;
+use chrono::{DateTime, Utc};
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
+use serde::{Deserialize, Serialize};
+
+/// 用户优惠券数据对象
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "t_user_coupon")]
+pub struct Model {
+    /// 用户优惠券ID，主键
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 用户ID
+    pub user_id: i64,
+
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+
+    /// 领取时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub receive_time: Option<DateTime<Utc>>,
+
+    /// 领取次数，同一用户对同一模板第几次领取
+    pub receive_count: i32,
+
+    /// 有效期开始时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub valid_start_time: Option<DateTime<Utc>>,
+
+    /// 有效期结束时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub valid_end_time: Option<DateTime<Utc>>,
+
+    /// 使用时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub use_time: Option<DateTime<Utc>>,
+
+    /// 券来源
+    pub source: UserCouponSource,
+
+    /// 状态
+    pub status: UserCouponStatus,
+
+    /// 创建时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub create_time: Option<DateTime<Utc>>,
+
+    /// 更新时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub update_time: Option<DateTime<Utc>>,
+
+    /// 删除标志
+    pub del_flag: i32,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::template::Entity",
+        from = "Column::CouponTemplateId",
+        to = "super::template::Column::Id"
+    )]
+    Template,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl SoftDelete for Entity {
+    fn del_flag() -> Column {
+        Column::DelFlag
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait, PrimaryKeyTrait};
+use serde::{Deserialize, Serialize};
+
+/// 用户弹框消息数据对象
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "t_user_popup")]
+pub struct Model {
+    /// 弹框消息ID，主键
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 用户ID
+    pub user_id: i64,
+
+    /// 标题
+    pub title: String,
+
+    /// 内容
+    pub content: String,
+
+    /// 已弹出标识 0：未弹出 1：已弹出
+    pub popped_flag: i32,
+
+    /// 创建时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub create_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,4 +1,4 @@
-use sea_orm::{DeriveActiveEnum, EnumIter};
+use sea_orm::{DeriveActiveEnum, EnumIter, Iterable};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 // --- 优惠券来源 ---
@@ -16,6 +16,26 @@ pub enum CouponSource {
     Platform = 1, // 平台券
 }
 
+impl CouponSource {
+    /// 中文名称，用于导出等展示场景
+    pub fn label(&self) -> &'static str {
+        match self {
+            CouponSource::Shop => "店铺券",
+            CouponSource::Platform => "平台券",
+        }
+    }
+
+    /// 是否需要审核：平台券需要审核通过后才能领取或发放
+    pub fn requires_audit(&self) -> bool {
+        *self == CouponSource::Platform
+    }
+
+    /// 根据中文名称查找枚举值，用于导入等场景
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::iter().find(|value| value.label() == label)
+    }
+}
+
 // --- 优惠对象 ---
 #[derive(
     Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
@@ -31,6 +51,21 @@ pub enum CouponTarget {
     StoreWide = 1, // 全店通用
 }
 
+impl CouponTarget {
+    /// 中文名称，用于导出等展示场景
+    pub fn label(&self) -> &'static str {
+        match self {
+            CouponTarget::SpecificGoods => "商品专属",
+            CouponTarget::StoreWide => "全店通用",
+        }
+    }
+
+    /// 根据中文名称查找枚举值，用于导入等场景
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::iter().find(|value| value.label() == label)
+    }
+}
+
 // --- 优惠类型 ---
 #[derive(
     Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
@@ -49,6 +84,22 @@ pub enum CouponType {
     Discount = 2, // 折扣券
 }
 
+impl CouponType {
+    /// 中文名称，用于导出等展示场景
+    pub fn label(&self) -> &'static str {
+        match self {
+            CouponType::InstantReduction => "立减券",
+            CouponType::FullReduction => "满减券",
+            CouponType::Discount => "折扣券",
+        }
+    }
+
+    /// 根据中文名称查找枚举值，用于导入等场景
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::iter().find(|value| value.label() == label)
+    }
+}
+
 // --- 优惠券状态 ---
 #[derive(
     Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
@@ -63,3 +114,257 @@ pub enum CouponStatus {
     #[sea_orm(num_value = 1)]
     Ended = 1, // 已结束
 }
+
+impl CouponStatus {
+    /// 中文名称，用于导出等展示场景
+    pub fn label(&self) -> &'static str {
+        match self {
+            CouponStatus::Active => "生效中",
+            CouponStatus::Ended => "已结束",
+        }
+    }
+
+    /// 根据中文名称查找枚举值，用于导入等场景
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::iter().find(|value| value.label() == label)
+    }
+}
+
+// --- 审核状态 ---
+#[derive(
+    Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+#[repr(i32)]
+pub enum AuditStatus {
+    #[sea_orm(num_value = 0)]
+    Draft = 0, // 草稿
+
+    #[sea_orm(num_value = 1)]
+    PendingReview = 1, // 待审核
+
+    #[default]
+    #[sea_orm(num_value = 2)]
+    Approved = 2, // 审核通过
+
+    #[sea_orm(num_value = 3)]
+    Rejected = 3, // 审核驳回
+}
+
+impl AuditStatus {
+    /// 中文名称，用于导出等展示场景
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuditStatus::Draft => "草稿",
+            AuditStatus::PendingReview => "待审核",
+            AuditStatus::Approved => "审核通过",
+            AuditStatus::Rejected => "审核驳回",
+        }
+    }
+
+    /// 新建模板的初始审核状态：平台券从草稿开始，需审核通过后才能领取或发放；店铺券无需审核
+    pub fn initial(source: &CouponSource) -> Self {
+        if source.requires_audit() {
+            AuditStatus::Draft
+        } else {
+            AuditStatus::Approved
+        }
+    }
+}
+
+// --- 用户优惠券来源 ---
+#[derive(
+    Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+#[repr(i32)]
+pub enum UserCouponSource {
+    #[default]
+    #[sea_orm(num_value = 0)]
+    ReceiveCenter = 0, // 领券中心
+
+    #[sea_orm(num_value = 1)]
+    PlatformDistribution = 1, // 平台发放
+
+    #[sea_orm(num_value = 2)]
+    ShopReceive = 2, // 店铺领取
+}
+
+// --- 用户优惠券状态 ---
+#[derive(
+    Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+#[repr(i32)]
+pub enum UserCouponStatus {
+    #[default]
+    #[sea_orm(num_value = 0)]
+    Unused = 0, // 未使用
+
+    #[sea_orm(num_value = 1)]
+    Locked = 1, // 锁定
+
+    #[sea_orm(num_value = 2)]
+    Used = 2, // 已使用
+
+    #[sea_orm(num_value = 3)]
+    Expired = 3, // 已过期
+
+    #[sea_orm(num_value = 4)]
+    Revoked = 4, // 已撤回
+}
+
+// --- 结算单状态 ---
+#[derive(
+    Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+#[repr(i32)]
+pub enum SettlementStatus {
+    #[default]
+    #[sea_orm(num_value = 0)]
+    Locked = 0, // 锁定
+
+    #[sea_orm(num_value = 1)]
+    Canceled = 1, // 已取消
+
+    #[sea_orm(num_value = 2)]
+    Paid = 2, // 已支付
+
+    #[sea_orm(num_value = 3)]
+    Refunded = 3, // 已退款
+}
+
+// --- 分发任务发送类型 ---
+#[derive(
+    Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+#[repr(i32)]
+pub enum TaskSendType {
+    #[default]
+    #[sea_orm(num_value = 0)]
+    Immediate = 0, // 立即发送
+
+    #[sea_orm(num_value = 1)]
+    Scheduled = 1, // 定时发送
+}
+
+impl TaskSendType {
+    /// 中文名称，用于导出等展示场景
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskSendType::Immediate => "立即发送",
+            TaskSendType::Scheduled => "定时发送",
+        }
+    }
+}
+
+// --- 分发任务状态 ---
+#[derive(
+    Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+#[repr(i32)]
+pub enum TaskStatus {
+    #[default]
+    #[sea_orm(num_value = 0)]
+    Pending = 0, // 待执行
+
+    #[sea_orm(num_value = 1)]
+    Running = 1, // 执行中
+
+    #[sea_orm(num_value = 2)]
+    Failed = 2, // 执行失败
+
+    #[sea_orm(num_value = 3)]
+    Succeeded = 3, // 执行成功
+
+    #[sea_orm(num_value = 4)]
+    Canceled = 4, // 取消
+}
+
+impl TaskStatus {
+    /// 中文名称，用于导出等展示场景
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "待执行",
+            TaskStatus::Running => "执行中",
+            TaskStatus::Failed => "执行失败",
+            TaskStatus::Succeeded => "执行成功",
+            TaskStatus::Canceled => "取消",
+        }
+    }
+
+    /// 是否为终止状态，终止后任务不会再被执行
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Failed | TaskStatus::Succeeded | TaskStatus::Canceled
+        )
+    }
+}
+
+// --- 通知方式 ---
+#[derive(
+    Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+#[repr(i32)]
+pub enum NotifyType {
+    #[default]
+    #[sea_orm(num_value = 0)]
+    InApp = 0, // 站内信
+
+    #[sea_orm(num_value = 1)]
+    Popup = 1, // 弹框推送
+
+    #[sea_orm(num_value = 2)]
+    Email = 2, // 邮箱
+
+    #[sea_orm(num_value = 3)]
+    Sms = 3, // 短信
+}
+
+impl NotifyType {
+    /// 中文名称，用于日志等展示场景
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotifyType::InApp => "站内信",
+            NotifyType::Popup => "弹框推送",
+            NotifyType::Email => "邮箱",
+            NotifyType::Sms => "短信",
+        }
+    }
+
+    /// 解析以逗号分隔的通知方式，例如分发任务的 `notify_type`，忽略无法识别和重复的值
+    pub fn parse_list(value: &str) -> Vec<Self> {
+        let mut types = Vec::new();
+        for code in value
+            .split(',')
+            .filter_map(|code| code.trim().parse::<i32>().ok())
+        {
+            if let Some(notify_type) = Self::iter().find(|value| value.clone() as i32 == code) {
+                if !types.contains(&notify_type) {
+                    types.push(notify_type);
+                }
+            }
+        }
+        types
+    }
+}
+
+// --- 通知失败记录状态 ---
+#[derive(
+    Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Default, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+#[repr(i32)]
+pub enum NotifyFailStatus {
+    #[default]
+    #[sea_orm(num_value = 0)]
+    Retrying = 0, // 等待重试
+
+    #[sea_orm(num_value = 1)]
+    Abandoned = 1, // 已放弃
+}
@@ -1,4 +1,6 @@
 pub mod dao;
 pub mod entity;
 pub mod enums;
+pub mod rule;
+pub mod soft_delete;
 
@@ -0,0 +1,333 @@
+use crate::enums::CouponType;
+use common::app_error::{AppError, FieldError};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// 金额字段允许的最大小数位数
+const AMOUNT_SCALE: u32 = 2;
+
+/// 规则校验不通过的字段
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleViolation {
+    /// 字段路径，例如 `consumeRule.discountRate`
+    pub field: String,
+    /// 错误描述
+    pub message: String,
+}
+
+impl RuleViolation {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        RuleViolation {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    /// 转换为 `VALIDATION_ERROR_CODE` 校验错误
+    pub fn into_error(self) -> AppError {
+        AppError::validation_error(format!("{}: {}", self.field, self.message))
+    }
+}
+
+impl From<RuleViolation> for FieldError {
+    fn from(violation: RuleViolation) -> Self {
+        FieldError::new(violation.field, violation.message)
+    }
+}
+
+/// 领取规则
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ReceiveRule {
+    /// 每人限领张数
+    pub limit_per_person: i32,
+
+    /// 使用说明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_instructions: Option<String>,
+}
+
+impl ReceiveRule {
+    const FIELD: &'static str = "receiveRule";
+
+    /// 严格解析 JSON 字符串，字段缺失、类型错误或存在未知字段时返回错误
+    pub fn parse(json: &str) -> Result<Self, RuleViolation> {
+        parse_str(Self::FIELD, json)
+    }
+
+    /// 严格解析已存储的 JSON 值
+    pub fn from_json(value: &JsonValue) -> Result<Self, RuleViolation> {
+        parse_value(Self::FIELD, value)
+    }
+
+    /// 返回全部校验不通过的字段
+    pub fn violations(&self) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        if self.limit_per_person < 1 {
+            violations.push(RuleViolation::new(
+                "receiveRule.limitPerPerson",
+                "每人限领张数必须大于0",
+            ));
+        }
+        violations
+    }
+
+    /// 校验规则，返回第一个不通过的字段
+    pub fn validate(&self) -> Result<(), AppError> {
+        first_violation(self.violations())
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::to_value(self).unwrap_or(JsonValue::Null)
+    }
+}
+
+/// 消耗规则
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ConsumeRule {
+    /// 使用门槛，订单金额满多少元可用
+    #[serde(
+        with = "rust_decimal::serde::float_option",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub terms_of_use: Option<Decimal>,
+
+    /// 优惠金额：立减券和满减券为减免金额，折扣券为最多优惠金额
+    #[serde(
+        with = "rust_decimal::serde::float_option",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub maximum_discount_amount: Option<Decimal>,
+
+    /// 折扣率，仅折扣券使用，例如 0.8 表示八折
+    #[serde(
+        with = "rust_decimal::serde::float_option",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub discount_rate: Option<Decimal>,
+
+    /// 使用说明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+
+    /// 领取后的有效时长 (小时)，为空时使用模板的有效期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validity_period: Option<i32>,
+}
+
+impl ConsumeRule {
+    const FIELD: &'static str = "consumeRule";
+
+    /// 严格解析 JSON 字符串，字段类型错误或存在未知字段时返回错误
+    pub fn parse(json: &str) -> Result<Self, RuleViolation> {
+        parse_str(Self::FIELD, json)
+    }
+
+    /// 严格解析已存储的 JSON 值
+    pub fn from_json(value: &JsonValue) -> Result<Self, RuleViolation> {
+        parse_value(Self::FIELD, value)
+    }
+
+    /// 按优惠类型返回全部校验不通过的字段
+    ///
+    /// * 立减券：必须填写减免金额，不能填写折扣率
+    /// * 满减券：必须填写使用门槛和减免金额，减免金额不能超过门槛，不能填写折扣率
+    /// * 折扣券：必须填写 (0, 1) 区间内的折扣率，最多优惠金额可选
+    pub fn violations(&self, coupon_type: &CouponType) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+
+        check_amount(
+            &mut violations,
+            "consumeRule.termsOfUse",
+            self.terms_of_use,
+            false,
+        );
+        check_amount(
+            &mut violations,
+            "consumeRule.maximumDiscountAmount",
+            self.maximum_discount_amount,
+            true,
+        );
+        if let Some(period) = self.validity_period {
+            if period <= 0 {
+                violations.push(RuleViolation::new(
+                    "consumeRule.validityPeriod",
+                    "有效时长必须大于0",
+                ));
+            }
+        }
+
+        match coupon_type {
+            CouponType::InstantReduction | CouponType::FullReduction => {
+                if self.maximum_discount_amount.is_none() {
+                    violations.push(RuleViolation::new(
+                        "consumeRule.maximumDiscountAmount",
+                        "减免金额不能为空",
+                    ));
+                }
+                if self.discount_rate.is_some() {
+                    violations.push(RuleViolation::new(
+                        "consumeRule.discountRate",
+                        "只有折扣券可以设置折扣率",
+                    ));
+                }
+                if *coupon_type == CouponType::FullReduction {
+                    match (self.terms_of_use, self.maximum_discount_amount) {
+                        (None, _) => violations.push(RuleViolation::new(
+                            "consumeRule.termsOfUse",
+                            "满减券的使用门槛不能为空",
+                        )),
+                        (Some(threshold), _) if threshold <= Decimal::ZERO => {
+                            violations.push(RuleViolation::new(
+                                "consumeRule.termsOfUse",
+                                "满减券的使用门槛必须大于0",
+                            ))
+                        }
+                        (Some(threshold), Some(amount)) if amount > threshold => {
+                            violations.push(RuleViolation::new(
+                                "consumeRule.maximumDiscountAmount",
+                                "减免金额不能超过使用门槛",
+                            ))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            CouponType::Discount => match self.discount_rate {
+                None => violations.push(RuleViolation::new(
+                    "consumeRule.discountRate",
+                    "折扣券的折扣率不能为空",
+                )),
+                Some(rate) if rate <= Decimal::ZERO || rate >= Decimal::ONE => {
+                    violations.push(RuleViolation::new(
+                        "consumeRule.discountRate",
+                        "折扣率必须在 0 到 1 之间 (不含边界)",
+                    ))
+                }
+                Some(_) => {}
+            },
+        }
+        violations
+    }
+
+    /// 按优惠类型校验规则，返回第一个不通过的字段
+    pub fn validate(&self, coupon_type: &CouponType) -> Result<(), AppError> {
+        first_violation(self.violations(coupon_type))
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::to_value(self).unwrap_or(JsonValue::Null)
+    }
+}
+
+/// 校验金额非负 (或为正) 且最多两位小数
+fn check_amount(
+    violations: &mut Vec<RuleViolation>,
+    field: &str,
+    amount: Option<Decimal>,
+    positive: bool,
+) {
+    let Some(amount) = amount else {
+        return;
+    };
+    if positive && amount <= Decimal::ZERO {
+        violations.push(RuleViolation::new(field, "金额必须大于0"));
+    } else if amount < Decimal::ZERO {
+        violations.push(RuleViolation::new(field, "金额不能小于0"));
+    } else if amount.normalize().scale() > AMOUNT_SCALE {
+        violations.push(RuleViolation::new(field, "金额最多保留两位小数"));
+    }
+}
+
+fn first_violation(violations: Vec<RuleViolation>) -> Result<(), AppError> {
+    match violations.into_iter().next() {
+        Some(violation) => Err(violation.into_error()),
+        None => Ok(()),
+    }
+}
+
+fn parse_str<T: DeserializeOwned>(root: &str, json: &str) -> Result<T, RuleViolation> {
+    let deserializer = &mut serde_json::Deserializer::from_str(json);
+    serde_path_to_error::deserialize(deserializer).map_err(|err| to_violation(root, err))
+}
+
+fn parse_value<T: DeserializeOwned>(root: &str, value: &JsonValue) -> Result<T, RuleViolation> {
+    serde_path_to_error::deserialize(value.clone()).map_err(|err| to_violation(root, err))
+}
+
+fn to_violation<E: std::fmt::Display>(
+    root: &str,
+    err: serde_path_to_error::Error<E>,
+) -> RuleViolation {
+    let path = err.path().to_string();
+    let field = if path == "." {
+        root.to_string()
+    } else {
+        format!("{}.{}", root, path)
+    };
+    RuleViolation::new(field, format!("格式错误: {}", err.inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_receive_rule_rejects_unknown_field() {
+        let err = ReceiveRule::parse(r#"{"limitPerPerson":1,"foo":2}"#).unwrap_err();
+        assert_eq!(err.field, "receiveRule.foo");
+    }
+
+    #[test]
+    fn parse_receive_rule_names_field_with_wrong_type() {
+        let err = ReceiveRule::parse(r#"{"limitPerPerson":"one"}"#).unwrap_err();
+        assert_eq!(err.field, "receiveRule.limitPerPerson");
+    }
+
+    #[test]
+    fn only_rule_amounts_serialize_as_numbers() {
+        let rule = ConsumeRule::parse(r#"{"termsOfUse":100.5,"discountRate":0.85}"#).unwrap();
+        assert_eq!(
+            rule.to_json(),
+            serde_json::json!({"termsOfUse": 100.5, "discountRate": 0.85})
+        );
+        assert_eq!(
+            serde_json::to_value(Decimal::new(1050, 2)).unwrap(),
+            JsonValue::from("10.50")
+        );
+    }
+
+    #[test]
+    fn full_reduction_requires_threshold() {
+        let rule = ConsumeRule::parse(r#"{"maximumDiscountAmount":3}"#).unwrap();
+        let violations = rule.violations(&CouponType::FullReduction);
+        assert_eq!(violations[0].field, "consumeRule.termsOfUse");
+    }
+
+    #[test]
+    fn discount_rate_must_be_between_zero_and_one() {
+        let rule = ConsumeRule::parse(r#"{"discountRate":1.2}"#).unwrap();
+        let err = rule.validate(&CouponType::Discount).unwrap_err();
+        assert!(err.message().starts_with("consumeRule.discountRate"));
+
+        let rule =
+            ConsumeRule::parse(r#"{"discountRate":0.85,"maximumDiscountAmount":20}"#).unwrap();
+        assert!(rule.validate(&CouponType::Discount).is_ok());
+    }
+
+    #[test]
+    fn consume_rule_round_trips_as_json_numbers() {
+        let rule = ConsumeRule::parse(r#"{"termsOfUse":10,"maximumDiscountAmount":3.5}"#).unwrap();
+        assert!(rule.validate(&CouponType::FullReduction).is_ok());
+        assert_eq!(
+            rule.to_json().to_string(),
+            r#"{"maximumDiscountAmount":3.5,"termsOfUse":10.0}"#
+        );
+    }
+}
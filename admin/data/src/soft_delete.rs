@@ -0,0 +1,58 @@
+use sea_orm::sea_query::SimpleExpr;
+use sea_orm::{ColumnTrait, EntityTrait, PrimaryKeyTrait, QueryFilter, Select, UpdateMany};
+
+/// 删除标识：未删除
+pub const NOT_DELETED: i32 = 0;
+/// 删除标识：已删除
+pub const DELETED: i32 = 1;
+
+/// 带 `del_flag` 逻辑删除字段的实体
+///
+/// sea-orm 的 `EntityTrait::find`、`find_by_id`、`update_many` 等方法无法被覆盖，
+/// 逻辑删除的过滤统一在 DAO 中完成：DAO 对这些实体的查询和更新都通过 `find_alive` / `update_alive` 发起，
+/// 关联查询通过 `alive` 条件排除已删除的记录，不直接调用 `find` 或 `update_many`。
+/// 确实需要访问已删除数据时，显式调用 `find_with_deleted`、`find_deleted` 或 `update_deleted`。
+///
+/// `del_flag` 字段在数据库中为 `NOT NULL DEFAULT 0`，未指定删除标识写入的记录也会被视为未删除
+pub trait SoftDelete: EntityTrait {
+    /// 逻辑删除字段
+    fn del_flag() -> Self::Column;
+
+    /// 未删除的记录的条件，用于以其他实体为主表的关联查询
+    fn alive() -> SimpleExpr {
+        Self::del_flag().eq(NOT_DELETED)
+    }
+
+    /// 查询未删除的记录
+    fn find_alive() -> Select<Self> {
+        Self::find().filter(Self::alive())
+    }
+
+    /// 根据主键查询未删除的记录
+    fn find_alive_by_id<T>(values: T) -> Select<Self>
+    where
+        T: Into<<Self::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+    {
+        Self::find_by_id(values).filter(Self::alive())
+    }
+
+    /// 查询全部记录，包括已删除的记录
+    fn find_with_deleted() -> Select<Self> {
+        Self::find()
+    }
+
+    /// 仅查询已删除的记录
+    fn find_deleted() -> Select<Self> {
+        Self::find().filter(Self::del_flag().eq(DELETED))
+    }
+
+    /// 批量更新未删除的记录
+    fn update_alive() -> UpdateMany<Self> {
+        Self::update_many().filter(Self::alive())
+    }
+
+    /// 批量更新已删除的记录，例如恢复
+    fn update_deleted() -> UpdateMany<Self> {
+        Self::update_many().filter(Self::del_flag().eq(DELETED))
+    }
+}
@@ -1 +1,3 @@
 pub const SHOP_NUMBER: i64 = 1810714735922956666;
+pub const OPERATOR_ID: i64 = 1810518709471555585;
+pub const USER_ID: i64 = 1810868149847928832;
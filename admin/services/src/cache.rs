@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 进程内带过期时间的缓存
+///
+/// 只适合缓存允许短时间不一致的数据，例如统计结果；多个实例之间不共享
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        TtlCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 获取未过期的缓存值
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(key)
+            .filter(|(expires_at, _)| *expires_at > Instant::now())
+            .map(|(_, value)| value.clone())
+    }
+
+    /// 写入缓存，同时清理已过期的条目
+    pub fn insert(&self, key: K, value: V) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (expires_at, _)| *expires_at > now);
+        entries.insert(key, (now + self.ttl, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let cache = TtlCache::new(Duration::from_millis(20));
+        cache.insert("shop", 1);
+        assert_eq!(cache.get(&"shop"), Some(1));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get(&"shop"), None);
+    }
+}
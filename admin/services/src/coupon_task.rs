@@ -0,0 +1,610 @@
+use crate::auth::{OPERATOR_ID, SHOP_NUMBER};
+use crate::coupon_task_executor;
+use crate::coupon_task_fail_file::{self, TaskFile};
+use crate::coupon_task_progress::{self, ProgressEvent, TaskProgress};
+use crate::dto::page_req::check_page;
+use crate::dto::task_req::{
+    TaskCreateReqDto, TaskIdReqDto, TaskPageQueryReqDto, TaskRescheduleReqDto,
+};
+use crate::template::ensure_owned;
+use crate::template_import::{is_xlsx, read_table};
+use crate::validation::Validate;
+use crate::AppState;
+use actix_web::web::{self, Data};
+use chrono::{DateTime, Utc};
+use common::app_error::AppError;
+use common::id_generator::next_id;
+use common::transfer::PageResult;
+use data::dao::coupon_task::coupon_task_dao;
+use data::dao::coupon_task_fail::coupon_task_fail_dao;
+use data::dao::template::template_dao;
+use data::entity::{coupon_task, template};
+use data::enums::{TaskSendType, TaskStatus};
+use data::soft_delete::NOT_DELETED;
+use futures_util::stream::BoxStream;
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::TransactionTrait;
+use std::time::Duration;
+
+/// 分发任务的用户文件和失败用户文件在存储中的目录
+pub(crate) const TASK_FILE_DIR: &str = "coupon-task";
+
+#[async_trait]
+pub trait CouponTaskService: Send + Sync {
+    async fn create_task(
+        &self,
+        file_name: Option<String>,
+        content: Vec<u8>,
+        req: TaskCreateReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<i64, AppError>;
+
+    async fn page_task(
+        &self,
+        req: TaskPageQueryReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<PageResult<coupon_task::Model>, AppError>;
+
+    async fn find_task(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<coupon_task::Model, AppError>;
+
+    async fn download_fail_file(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<TaskFile, AppError>;
+
+    async fn fail_file_link(&self, id: i64, app_state: Data<AppState>) -> Result<String, AppError>;
+
+    async fn cancel_task(
+        &self,
+        req: TaskIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError>;
+
+    async fn reschedule_task(
+        &self,
+        req: TaskRescheduleReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError>;
+
+    async fn retry_task(
+        &self,
+        req: TaskIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError>;
+
+    async fn find_progress(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<TaskProgress, AppError>;
+
+    async fn watch_progress(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<BoxStream<'static, ProgressEvent>, AppError>;
+}
+
+pub struct CouponTaskServiceImpl;
+
+#[async_trait]
+impl CouponTaskService for CouponTaskServiceImpl {
+    /// 根据上传的用户文件创建优惠券分发任务
+    ///
+    /// 用户文件为 CSV 或 XLSX，第一列为用户ID，第一行不是数字时作为表头跳过。
+    /// 文件保存到配置的文件存储后，任务以待执行状态创建；立即发送的任务创建后马上开始执行
+    ///
+    /// # 参数
+    /// * `file_name` - 上传的文件名，用于判断文件格式
+    /// * `content` - 上传的文件内容
+    /// * `req` - 分发任务创建请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接和配置
+    ///
+    /// # 返回
+    /// * `Result<i64, AppError>` - 成功时返回任务ID，文件或模板校验不通过时返回错误
+    async fn create_task(
+        &self,
+        file_name: Option<String>,
+        content: Vec<u8>,
+        req: TaskCreateReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<i64, AppError> {
+        req.validate()?;
+
+        let extension = if is_xlsx(file_name.as_deref(), &content) {
+            "xlsx"
+        } else {
+            "csv"
+        };
+        // 解析 XLSX 文件较慢，在阻塞线程池中执行，解析完成后取回文件内容用于保存
+        let (users, content) = web::block(move || {
+            let users = parse_users(file_name.as_deref(), &content);
+            (users, content)
+        })
+        .await
+        .map_err(AppError::internal_error)?;
+        let users = users?;
+        if users.is_empty() {
+            return Err(AppError::validation_error("用户文件中没有用户"));
+        }
+        let send_num = i32::try_from(users.len())
+            .map_err(|_| AppError::validation_error("用户文件中的用户数量过多"))?;
+
+        let send_time = match req.send_type {
+            TaskSendType::Immediate => Utc::now(),
+            TaskSendType::Scheduled => req.send_time.unwrap_or_else(Utc::now),
+        };
+        let template = template_dao()
+            .find_by_id(&app_state.database, req.coupon_template_id)
+            .await?;
+        let template = ensure_owned(template, req.coupon_template_id)?;
+        check_distributable(&template, send_num, send_time)?;
+
+        // 先保存文件再创建任务，任务创建失败时删除已保存的文件
+        let batch_id = next_id();
+        let file_address = app_state
+            .storage
+            .upload(
+                &format!("{}/{}.{}", TASK_FILE_DIR, batch_id, extension),
+                content,
+            )
+            .await?;
+
+        let now = Utc::now();
+        let task = coupon_task::Model {
+            id: 0,
+            shop_number: SHOP_NUMBER, //TODO: 需要实现用户登录模块
+            batch_id,
+            task_name: req.task_name.trim().to_string(),
+            file_address: file_address.clone(),
+            fail_file_address: None,
+            file_cleaned: 0,
+            send_num,
+            processed_num: 0,
+            success_num: 0,
+            fail_num: 0,
+            retry_fail_id: None,
+            notify_type: req
+                .notify_type
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+                .filter(|value| !value.is_empty()),
+            coupon_template_id: template.id,
+            send_type: req.send_type,
+            send_time: Some(send_time),
+            status: TaskStatus::Pending,
+            completion_time: None,
+            create_time: Some(now),
+            operator_id: Some(OPERATOR_ID), //TODO: 需要实现用户登录模块
+            update_time: Some(now),
+            del_flag: NOT_DELETED,
+        };
+        let created = match insert(&task, &app_state).await {
+            Ok(created) => created,
+            Err(err) => {
+                if let Err(remove_err) = app_state.storage.delete(&file_address).await {
+                    warn!("删除用户文件失败: {}, 错误: {}", file_address, remove_err);
+                }
+                return Err(err);
+            }
+        };
+
+        info!(
+            "创建优惠券分发任务成功, 任务ID: {}, 批次ID: {}, 模板ID: {}, 发放数量: {}",
+            created.id, created.batch_id, created.coupon_template_id, created.send_num
+        );
+        if created.send_type == TaskSendType::Immediate {
+            coupon_task_executor::dispatch(created.id, app_state);
+        }
+        Ok(created.id)
+    }
+
+    /// 分页查询当前店铺的优惠券分发任务
+    ///
+    /// # 参数
+    /// * `req` - 分页查询请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<PageResult<coupon_task::Model>, AppError>` - 成功时返回分页结果，单页数量超过上限时返回错误
+    async fn page_task(
+        &self,
+        req: TaskPageQueryReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<PageResult<coupon_task::Model>, AppError> {
+        check_page(req.current, req.size)?;
+
+        let filter = req.to_filter(SHOP_NUMBER); //TODO: 需要实现用户登录模块
+        let (records, total) = coupon_task_dao()
+            .page(&app_state.database, &filter, req.current, req.size)
+            .await
+            .map_err(|err| {
+                error!("分页查询优惠券分发任务失败: {}", err);
+                AppError::from(err)
+            })?;
+
+        Ok(PageResult::new(req.current, req.size, total, records))
+    }
+
+    /// 查询当前店铺的优惠券分发任务详情
+    ///
+    /// 任务不存在、已删除或不属于当前店铺时，统一按未找到处理
+    ///
+    /// # 参数
+    /// * `id` - 分发任务ID
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<coupon_task::Model, AppError>` - 成功时返回任务详情，失败时返回错误
+    async fn find_task(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<coupon_task::Model, AppError> {
+        let task = coupon_task_dao()
+            .find_by_id(&app_state.database, id)
+            .await
+            .map_err(|err| {
+                error!("查询优惠券分发任务失败, 任务ID: {}, 错误: {}", id, err);
+                AppError::from(err)
+            })?;
+
+        ensure_own_task(task, id)
+    }
+
+    /// 下载分发任务的发放失败用户文件
+    ///
+    /// 文件包含用户ID和失败原因，格式与上传的用户文件一致，修改后可以作为新任务的用户文件重新上传。
+    /// 任务结束时没有生成文件、文件已被清理或已丢失的，下载时重新生成
+    ///
+    /// # 参数
+    /// * `id` - 分发任务ID
+    /// * `app_state` - 应用程序状态，包含数据库连接和配置
+    ///
+    /// # 返回
+    /// * `Result<TaskFile, AppError>` - 成功时返回文件内容，任务未结束或没有失败用户时返回错误
+    async fn download_fail_file(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<TaskFile, AppError> {
+        let task = self.find_task(id, app_state.clone()).await?;
+        check_fail_file(&task)?;
+
+        let content = match &task.fail_file_address {
+            Some(address) => match app_state.storage.download(address).await {
+                Ok(content) => Some(content),
+                Err(err) => {
+                    warn!("读取失败用户文件失败, 任务ID: {}, 错误: {}", id, err);
+                    None
+                }
+            },
+            None => None,
+        };
+        let content = match content {
+            Some(content) => content,
+            None => {
+                let address = coupon_task_fail_file::generate(&task, &app_state).await?;
+                app_state.storage.download(&address).await?
+            }
+        };
+
+        Ok(TaskFile {
+            file_name: coupon_task_fail_file::download_name(&task),
+            content_type: coupon_task_fail_file::format_of(&task).content_type(),
+            content,
+        })
+    }
+
+    /// 生成分发任务发放失败用户文件的临时下载链接
+    ///
+    /// 链接在配置的有效期内不需要登录即可下载，没有生成文件或文件已被清理的先重新生成
+    ///
+    /// # 参数
+    /// * `id` - 分发任务ID
+    /// * `app_state` - 应用程序状态，包含数据库连接、配置和文件存储
+    ///
+    /// # 返回
+    /// * `Result<String, AppError>` - 成功时返回下载链接，任务未结束或没有失败用户时返回错误
+    async fn fail_file_link(&self, id: i64, app_state: Data<AppState>) -> Result<String, AppError> {
+        let task = self.find_task(id, app_state.clone()).await?;
+        check_fail_file(&task)?;
+
+        let address = match &task.fail_file_address {
+            Some(address) => address.clone(),
+            None => coupon_task_fail_file::generate(&task, &app_state).await?,
+        };
+        app_state.storage.signed_url(
+            &address,
+            Duration::from_secs(app_state.config.storage.link_expire_seconds),
+        )
+    }
+
+    /// 取消待执行或执行中的分发任务
+    ///
+    /// 任务行加锁后校验状态，与到达发送时间开始执行以及每批发放互斥。待执行的任务不会再被执行；
+    /// 执行中的任务在当前批次提交后停止，已发放的优惠券保留
+    ///
+    /// # 参数
+    /// * `req` - 分发任务ID请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<(), AppError>` - 成功时返回空，任务不存在或不是待执行或执行中状态时返回错误
+    async fn cancel_task(
+        &self,
+        req: TaskIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError> {
+        let id = req.coupon_task_id;
+        let txn = app_state.database.begin().await?;
+        let task = coupon_task_dao().find_by_id_for_update(&txn, id).await?;
+        let task = ensure_own_task(task, id)?;
+        if !matches!(task.status, TaskStatus::Pending | TaskStatus::Running) {
+            return Err(AppError::validation_error(format!(
+                "分发任务当前状态为{}，只有待执行或执行中的任务可以取消",
+                task.status.label()
+            )));
+        }
+        coupon_task_dao()
+            .transit(&txn, id, task.status.clone(), TaskStatus::Canceled)
+            .await?;
+        txn.commit().await?;
+        coupon_task_progress::publish(id);
+
+        info!(
+            "取消优惠券分发任务成功, 任务ID: {}, 已处理: {}/{}",
+            id, task.processed_num, task.send_num
+        );
+        Ok(())
+    }
+
+    /// 修改尚未开始执行的定时分发任务的发送时间
+    ///
+    /// # 参数
+    /// * `req` - 修改发送时间请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<(), AppError>` - 成功时返回空，任务不是待执行的定时任务或模板在新的发送时间已过有效期时返回错误
+    async fn reschedule_task(
+        &self,
+        req: TaskRescheduleReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError> {
+        req.validate()?;
+        let id = req.coupon_task_id;
+        let send_time = req.send_time.unwrap_or_else(Utc::now);
+
+        let txn = app_state.database.begin().await?;
+        let task = coupon_task_dao().find_by_id_for_update(&txn, id).await?;
+        let task = ensure_own_task(task, id)?;
+        if task.send_type != TaskSendType::Scheduled {
+            return Err(AppError::validation_error(
+                "立即发送的分发任务不能修改发送时间",
+            ));
+        }
+        if task.status != TaskStatus::Pending {
+            return Err(AppError::validation_error(format!(
+                "分发任务当前状态为{}，只有待执行的任务可以修改发送时间",
+                task.status.label()
+            )));
+        }
+        let template = template_dao()
+            .find_by_id(&app_state.database, task.coupon_template_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("优惠券模板", task.coupon_template_id))?;
+        if template.valid_end_time.is_some_and(|end| end <= send_time) {
+            return Err(AppError::validation_error("优惠券模板在发送时间已过有效期"));
+        }
+        coupon_task_dao().reschedule(&txn, id, send_time).await?;
+        txn.commit().await?;
+
+        info!(
+            "修改优惠券分发任务发送时间成功, 任务ID: {}, 发送时间: {}",
+            id, send_time
+        );
+        Ok(())
+    }
+
+    /// 重试执行失败的分发任务
+    ///
+    /// 只重新发放发放失败记录中的用户，用户ID无效或重复的用户保持失败；因执行出错中断的任务同时继续处理
+    /// 剩余的用户。任务行加锁后校验状态并修改为执行中，重复提交时只有一次生效，重试结束后重新生成失败用户文件
+    ///
+    /// # 参数
+    /// * `req` - 分发任务ID请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接和配置
+    ///
+    /// # 返回
+    /// * `Result<(), AppError>` - 成功时返回空，任务不是执行失败状态或没有需要重试的用户时返回错误
+    async fn retry_task(
+        &self,
+        req: TaskIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError> {
+        let id = req.coupon_task_id;
+        let txn = app_state.database.begin().await?;
+        let task = coupon_task_dao().find_by_id_for_update(&txn, id).await?;
+        let task = ensure_own_task(task, id)?;
+        if task.status != TaskStatus::Failed {
+            return Err(AppError::validation_error(format!(
+                "分发任务当前状态为{}，只有执行失败的任务可以重试",
+                task.status.label()
+            )));
+        }
+        if task.file_cleaned != 0 && task.processed_num < task.send_num {
+            return Err(AppError::validation_error(
+                "分发任务的用户文件已超过保留期限被清理，无法继续发放",
+            ));
+        }
+        let retry_fail_id = coupon_task_fail_dao().max_id(&txn, task.batch_id).await?;
+        if retry_fail_id.is_none() && task.processed_num >= task.send_num {
+            return Err(AppError::validation_error("分发任务没有需要重试的用户"));
+        }
+        coupon_task_dao()
+            .start_retry(&txn, id, retry_fail_id)
+            .await?;
+        txn.commit().await?;
+        coupon_task_progress::publish(id);
+
+        info!(
+            "重试优惠券分发任务, 任务ID: {}, 失败数量: {}, 已处理: {}/{}",
+            id, task.fail_num, task.processed_num, task.send_num
+        );
+        coupon_task_executor::dispatch_running(id, app_state);
+        Ok(())
+    }
+
+    /// 查询当前店铺分发任务的执行进度
+    ///
+    /// # 参数
+    /// * `id` - 分发任务ID
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<TaskProgress, AppError>` - 成功时返回已处理、成功、失败数量和用户总数，任务不存在时返回错误
+    async fn find_progress(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<TaskProgress, AppError> {
+        let task = self.find_task(id, app_state).await?;
+        Ok(TaskProgress::from(&task))
+    }
+
+    /// 订阅当前店铺分发任务的执行进度
+    ///
+    /// 返回的流首先推送当前进度，之后在每批发放提交或任务状态变化时推送，进度长时间没有变化时推送心跳；
+    /// 任务结束、尚未到发送时间或超过最长持续时间后结束
+    ///
+    /// # 参数
+    /// * `id` - 分发任务ID
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<BoxStream<'static, ProgressEvent>, AppError>` - 成功时返回进度流，任务不存在时返回错误
+    async fn watch_progress(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<BoxStream<'static, ProgressEvent>, AppError> {
+        self.find_task(id, app_state.clone()).await?;
+        Ok(coupon_task_progress::watch(id, app_state))
+    }
+}
+
+/// 校验任务存在且属于当前店铺，否则按未找到处理
+fn ensure_own_task(
+    task: Option<coupon_task::Model>,
+    id: i64,
+) -> Result<coupon_task::Model, AppError> {
+    match task {
+        Some(model) if model.shop_number == SHOP_NUMBER => Ok(model), //TODO: 需要实现用户登录模块
+        _ => Err(AppError::not_found("优惠券分发任务", id)),
+    }
+}
+
+/// 校验模板可以在发送时间向 `send_num` 个用户发放
+fn check_distributable(
+    template: &template::Model,
+    send_num: i32,
+    send_time: DateTime<Utc>,
+) -> Result<(), AppError> {
+    if !template.is_issuable() {
+        return Err(AppError::validation_error(
+            "优惠券模板未生效或未审核通过，不能发放",
+        ));
+    }
+    if template.valid_end_time.is_some_and(|end| end <= send_time) {
+        return Err(AppError::validation_error("优惠券模板在发送时间已过有效期"));
+    }
+    if template.stock < send_num {
+        return Err(AppError::validation_error(format!(
+            "优惠券模板库存不足，当前库存: {}，发放数量: {}",
+            template.stock, send_num
+        )));
+    }
+    Ok(())
+}
+
+async fn insert(
+    task: &coupon_task::Model,
+    app_state: &Data<AppState>,
+) -> Result<coupon_task::Model, AppError> {
+    let txn = app_state.database.begin().await?;
+    let created = coupon_task_dao().create(&txn, task).await.map_err(|err| {
+        error!("创建优惠券分发任务失败: {}", err);
+        AppError::from(err)
+    })?;
+    txn.commit().await?;
+    Ok(created)
+}
+
+/// 读取用户文件第一列的用户ID，跳过空行；第一行不是数字时视为表头
+///
+/// 返回原始文本，用户ID是否有效由执行任务时逐个校验
+pub(crate) fn parse_users(
+    file_name: Option<&str>,
+    content: &[u8],
+) -> Result<Vec<String>, AppError> {
+    let mut users: Vec<String> = read_table(file_name, content)?
+        .into_iter()
+        .filter_map(|row| row.into_iter().next())
+        .map(|cell| cell.trim().trim_start_matches('\u{feff}').to_string())
+        .filter(|cell| !cell.is_empty())
+        .collect();
+    if users
+        .first()
+        .is_some_and(|cell| cell.parse::<i64>().is_err())
+    {
+        users.remove(0);
+    }
+    Ok(users)
+}
+
+/// 校验任务可以下载发放失败用户文件：任务已结束且有失败用户
+fn check_fail_file(task: &coupon_task::Model) -> Result<(), AppError> {
+    if !task.status.is_terminal() {
+        return Err(AppError::validation_error("分发任务尚未执行结束"));
+    }
+    if task.fail_num == 0 {
+        return Err(AppError::validation_error("分发任务没有发放失败的用户"));
+    }
+    Ok(())
+}
+
+static COUPON_TASK_SERVICE: Lazy<CouponTaskServiceImpl> = Lazy::new(|| CouponTaskServiceImpl);
+
+pub fn coupon_task_service() -> &'static dyn CouponTaskService {
+    &*COUPON_TASK_SERVICE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_first_column_and_skips_header() {
+        let content = "\u{feff}用户ID,备注\n1001,a\n\n 1002 \nabc\n"
+            .as_bytes()
+            .to_vec();
+        let users = parse_users(Some("users.csv"), &content).unwrap();
+        assert_eq!(users, vec!["1001", "1002", "abc"]);
+
+        let users = parse_users(Some("users.csv"), b"1001\n1002\n").unwrap();
+        assert_eq!(users, vec!["1001", "1002"]);
+    }
+}
@@ -0,0 +1,668 @@
+use crate::coupon_task::parse_users;
+use crate::coupon_task_fail_file;
+use crate::coupon_task_progress;
+use crate::notify;
+use crate::AppState;
+use actix_web::web::{self, Data};
+use chrono::{Duration, Utc};
+use common::app_error::AppError;
+use data::dao::coupon_task::coupon_task_dao;
+use data::dao::coupon_task_fail::coupon_task_fail_dao;
+use data::dao::template::template_dao;
+use data::dao::user_coupon::user_coupon_dao;
+use data::entity::{coupon_task, coupon_task_fail, template, user_coupon};
+use data::enums::{TaskStatus, UserCouponSource, UserCouponStatus};
+use data::soft_delete::NOT_DELETED;
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{DatabaseTransaction, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 发放失败原因
+const REASON_INVALID_USER: &str = "用户ID无效";
+const REASON_DUPLICATE_USER: &str = "用户重复";
+const REASON_NOT_ISSUABLE: &str = "优惠券模板已结束、未审核通过或已过有效期";
+const REASON_OUT_OF_STOCK: &str = "优惠券模板库存不足";
+
+/// 发放失败记录的内容，以 JSON 保存在发放失败记录表中
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskFailRecord {
+    /// 用户文件中的用户ID原文
+    pub user_id: String,
+    /// 失败原因
+    pub reason: String,
+}
+
+impl TaskFailRecord {
+    fn new(user_id: impl ToString, reason: &str) -> Self {
+        TaskFailRecord {
+            user_id: user_id.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+pub trait CouponTaskExecutor: Send + Sync {
+    async fn execute(&self, task_id: i64, app_state: Data<AppState>) -> Result<(), AppError>;
+
+    async fn resume_running(&self, app_state: Data<AppState>) -> Result<usize, AppError>;
+
+    async fn dispatch_due_tasks(
+        &self,
+        batch_size: u64,
+        app_state: Data<AppState>,
+    ) -> Result<usize, AppError>;
+
+    async fn cleanup_task_files(
+        &self,
+        retention_days: i64,
+        batch_size: u64,
+        app_state: Data<AppState>,
+    ) -> Result<usize, AppError>;
+}
+
+pub struct CouponTaskExecutorImpl;
+
+#[async_trait]
+impl CouponTaskExecutor for CouponTaskExecutorImpl {
+    /// 执行优惠券分发任务
+    ///
+    /// 任务从待执行修改为执行中后，按用户文件中的顺序分批发放，每批在一个事务中扣减库存、
+    /// 写入用户优惠券和发放失败记录并累加已处理数量。进程重启后执行中的任务从已处理数量处继续，
+    /// 已提交的批次不会重复发放。全部处理完成后，没有失败用户的任务修改为执行成功，否则为执行失败
+    ///
+    /// # 参数
+    /// * `task_id` - 分发任务ID
+    /// * `app_state` - 应用程序状态，包含数据库连接和配置
+    ///
+    /// # 返回
+    /// * `Result<(), AppError>` - 任务不是待执行状态时直接返回成功，执行出错时任务修改为执行失败并返回错误
+    async fn execute(&self, task_id: i64, app_state: Data<AppState>) -> Result<(), AppError> {
+        let txn = app_state.database.begin().await?;
+        let claimed = coupon_task_dao()
+            .transit(&txn, task_id, TaskStatus::Pending, TaskStatus::Running)
+            .await?;
+        txn.commit().await?;
+
+        // 任务已被取消或已由其他调用开始执行
+        if claimed == 0 {
+            info!("优惠券分发任务不是待执行状态，跳过, 任务ID: {}", task_id);
+            return Ok(());
+        }
+        coupon_task_progress::publish(task_id);
+        proceed(task_id, &app_state).await
+    }
+
+    /// 恢复执行中的分发任务
+    ///
+    /// 服务启动时调用，继续执行上次停止时未完成的任务
+    ///
+    /// # 参数
+    /// * `app_state` - 应用程序状态，包含数据库连接和配置
+    ///
+    /// # 返回
+    /// * `Result<usize, AppError>` - 成功时返回恢复的任务数量，失败时返回错误
+    async fn resume_running(&self, app_state: Data<AppState>) -> Result<usize, AppError> {
+        let tasks = coupon_task_dao()
+            .list_by_status(&app_state.database, TaskStatus::Running)
+            .await?;
+        for task in &tasks {
+            info!(
+                "恢复执行优惠券分发任务, 任务ID: {}, 已处理: {}/{}",
+                task.id, task.processed_num, task.send_num
+            );
+            dispatch_running(task.id, app_state.clone());
+        }
+        Ok(tasks.len())
+    }
+
+    /// 开始执行已到发送时间的待执行分发任务
+    ///
+    /// 由定时任务周期调用。到期任务在一个事务中加锁并修改为执行中，已被其他实例锁定的任务会被跳过，
+    /// 提交后再在后台执行，同一个任务不会被重复开始。任务状态保存在数据库中，服务重启期间到期的任务
+    /// 在重启后的下一次调用时开始执行
+    ///
+    /// # 参数
+    /// * `batch_size` - 每次最多开始执行的任务数量
+    /// * `app_state` - 应用程序状态，包含数据库连接和配置
+    ///
+    /// # 返回
+    /// * `Result<usize, AppError>` - 成功时返回开始执行的任务数量，失败时返回错误
+    async fn dispatch_due_tasks(
+        &self,
+        batch_size: u64,
+        app_state: Data<AppState>,
+    ) -> Result<usize, AppError> {
+        let txn = app_state.database.begin().await?;
+        let tasks = coupon_task_dao()
+            .lock_due(&txn, Utc::now(), batch_size)
+            .await?;
+        let mut claimed = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let rows = coupon_task_dao()
+                .transit(&txn, task.id, TaskStatus::Pending, TaskStatus::Running)
+                .await?;
+            if rows > 0 {
+                claimed.push(task.id);
+            }
+        }
+        txn.commit().await?;
+
+        for task_id in &claimed {
+            info!("定时分发任务到达发送时间，开始执行, 任务ID: {}", task_id);
+            coupon_task_progress::publish(*task_id);
+            dispatch_running(*task_id, app_state.clone());
+        }
+        Ok(claimed.len())
+    }
+
+    /// 清理已结束超过保留天数的分发任务文件
+    ///
+    /// 由定时任务周期调用。每个任务在行锁内删除用户文件和失败用户文件并标记用户文件已清理，
+    /// 与重试互斥；用户文件已清理但未处理完的任务不能再重试。删除失败的任务跳过，下次调用时重新清理
+    ///
+    /// # 参数
+    /// * `retention_days` - 任务结束后文件保留的天数
+    /// * `batch_size` - 每次最多清理的任务数量
+    /// * `app_state` - 应用程序状态，包含数据库连接和文件存储
+    ///
+    /// # 返回
+    /// * `Result<usize, AppError>` - 成功时返回清理的任务数量，失败时返回错误
+    async fn cleanup_task_files(
+        &self,
+        retention_days: i64,
+        batch_size: u64,
+        app_state: Data<AppState>,
+    ) -> Result<usize, AppError> {
+        let before = Utc::now() - Duration::days(retention_days.max(0));
+        let tasks = coupon_task_dao()
+            .list_files_to_clean(&app_state.database, before, batch_size)
+            .await?;
+        let mut cleaned = 0;
+        for task in tasks {
+            match cleanup_files(task.id, &app_state).await {
+                Ok(true) => cleaned += 1,
+                Ok(false) => {}
+                Err(err) => warn!("清理分发任务文件失败, 任务ID: {}, 错误: {}", task.id, err),
+            }
+        }
+        Ok(cleaned)
+    }
+}
+
+/// 在任务行锁内删除任务文件，任务已不是结束状态时跳过并返回 `false`
+async fn cleanup_files(task_id: i64, app_state: &Data<AppState>) -> Result<bool, AppError> {
+    let txn = app_state.database.begin().await?;
+    let task = match coupon_task_dao()
+        .find_by_id_for_update(&txn, task_id)
+        .await?
+    {
+        Some(task) if task.status.is_terminal() => task,
+        _ => return Ok(false),
+    };
+    if task.file_cleaned == 0 {
+        app_state.storage.delete(&task.file_address).await?;
+    }
+    if let Some(address) = &task.fail_file_address {
+        app_state.storage.delete(address).await?;
+    }
+    coupon_task_dao().mark_file_cleaned(&txn, task_id).await?;
+    txn.commit().await?;
+    Ok(true)
+}
+
+/// 在后台执行分发任务，不等待执行结果
+pub(crate) fn dispatch(task_id: i64, app_state: Data<AppState>) {
+    tokio::spawn(async move {
+        if let Err(err) = coupon_task_executor().execute(task_id, app_state).await {
+            error!("优惠券分发任务执行失败, 任务ID: {}, 错误: {}", task_id, err);
+        }
+    });
+}
+
+/// 在后台继续执行已修改为执行中的分发任务，不等待执行结果
+pub(crate) fn dispatch_running(task_id: i64, app_state: Data<AppState>) {
+    tokio::spawn(async move {
+        if let Err(err) = proceed(task_id, &app_state).await {
+            error!("优惠券分发任务执行失败, 任务ID: {}, 错误: {}", task_id, err);
+        }
+    });
+}
+
+/// 执行处于执行中状态的任务，出错时修改为执行失败
+async fn proceed(task_id: i64, app_state: &Data<AppState>) -> Result<(), AppError> {
+    let task = coupon_task_dao()
+        .find_by_id(&app_state.database, task_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("优惠券分发任务", task_id))?;
+    if task.status != TaskStatus::Running {
+        info!(
+            "优惠券分发任务状态为{}，跳过, 任务ID: {}",
+            task.status.label(),
+            task_id
+        );
+        return Ok(());
+    }
+
+    let result = match run(&task, app_state).await {
+        Ok(true) => retry(&task, app_state).await,
+        other => other,
+    };
+    match result {
+        Ok(true) => finish(task_id, app_state).await,
+        Ok(false) => Ok(()),
+        Err(err) => {
+            let txn = app_state.database.begin().await?;
+            coupon_task_dao()
+                .transit(&txn, task_id, TaskStatus::Running, TaskStatus::Failed)
+                .await?;
+            txn.commit().await?;
+            coupon_task_progress::publish(task_id);
+            Err(err)
+        }
+    }
+}
+
+/// 从任务的已处理数量开始逐批发放，全部处理完成时返回 `true`，任务不再是执行中状态时停止并返回 `false`
+async fn run(task: &coupon_task::Model, app_state: &Data<AppState>) -> Result<bool, AppError> {
+    // 用户文件已全部处理的任务 (例如重试失败用户) 不再读取文件，文件可能已被清理
+    if task.processed_num >= task.send_num {
+        return Ok(true);
+    }
+    let content = app_state.storage.download(&task.file_address).await?;
+    let file_address = task.file_address.clone();
+    let users = web::block(move || parse_users(Some(&file_address), &content))
+        .await
+        .map_err(AppError::internal_error)??;
+    let chunk_size = app_state.config.task.chunk_size.max(1);
+
+    // 已出现过的有效用户ID，用于识别重复用户；恢复执行时先补齐已处理部分
+    let mut seen = HashSet::new();
+    let mut scanned = 0;
+    loop {
+        let txn = app_state.database.begin().await?;
+        let current = match coupon_task_dao()
+            .find_by_id_for_update(&txn, task.id)
+            .await?
+        {
+            Some(current) if current.status == TaskStatus::Running => current,
+            _ => {
+                info!("优惠券分发任务已不在执行中，停止执行, 任务ID: {}", task.id);
+                return Ok(false);
+            }
+        };
+        let offset = usize::try_from(current.processed_num).unwrap_or(0);
+        if offset >= users.len() {
+            return Ok(true);
+        }
+        let (end, user_ids, mut fails) = next_chunk(&users, scanned, offset, chunk_size, &mut seen);
+        scanned = end;
+
+        let issued = issue(&txn, task.coupon_template_id, user_ids, &mut fails).await?;
+        let records: Vec<coupon_task_fail::Model> = fails
+            .iter()
+            .map(|fail| coupon_task_fail::Model {
+                id: 0,
+                batch_id: task.batch_id,
+                json_object: serde_json::to_string(fail).unwrap_or_default(),
+            })
+            .collect();
+        coupon_task_fail_dao().create_batch(&txn, &records).await?;
+        coupon_task_dao()
+            .advance(
+                &txn,
+                task.id,
+                (end - offset) as i32,
+                issued.len() as i32,
+                records.len() as i32,
+            )
+            .await?;
+        txn.commit().await?;
+        coupon_task_progress::publish(task.id);
+        notify::dispatch(&current, issued, app_state.clone());
+    }
+}
+
+/// 取出从 `offset` 开始的一批用户并分类，返回 (这批用户的结束位置, 有效用户ID, 发放失败的用户)
+///
+/// `scanned` 之前的用户已记入 `seen`；恢复执行时 `offset` 可能大于 `scanned`，先将中间已处理的用户记入 `seen`，
+/// 保证与已处理部分重复的用户同样被识别
+fn next_chunk(
+    users: &[String],
+    scanned: usize,
+    offset: usize,
+    chunk_size: usize,
+    seen: &mut HashSet<i64>,
+) -> (usize, Vec<i64>, Vec<TaskFailRecord>) {
+    for user in users.get(scanned..offset).unwrap_or_default() {
+        if let Ok(user_id) = user.parse::<i64>() {
+            seen.insert(user_id);
+        }
+    }
+    let end = users.len().min(offset + chunk_size);
+    let (user_ids, fails) = classify(users.get(offset..end).unwrap_or_default(), seen);
+    (end, user_ids, fails)
+}
+
+/// 重新处理任务重试时记录的发放失败用户，全部处理完成时返回 `true`，任务不再是执行中状态时停止并返回 `false`
+///
+/// 每批在一个事务中删除 ID 不大于 `retry_fail_id` 的失败记录并重新发放，仍然失败的用户写入新的失败记录，
+/// 重启后继续处理剩余的记录。用户ID无效和重复的记录原样保留
+async fn retry(task: &coupon_task::Model, app_state: &Data<AppState>) -> Result<bool, AppError> {
+    let chunk_size = app_state.config.task.chunk_size.max(1) as u64;
+    loop {
+        let txn = app_state.database.begin().await?;
+        let current = match coupon_task_dao()
+            .find_by_id_for_update(&txn, task.id)
+            .await?
+        {
+            Some(current) if current.status == TaskStatus::Running => current,
+            _ => {
+                info!("优惠券分发任务已不在执行中，停止重试, 任务ID: {}", task.id);
+                return Ok(false);
+            }
+        };
+        let Some(retry_fail_id) = current.retry_fail_id else {
+            return Ok(true);
+        };
+        let records = coupon_task_fail_dao()
+            .list_until(&txn, task.batch_id, retry_fail_id, chunk_size)
+            .await?;
+        if records.is_empty() {
+            return Ok(true);
+        }
+
+        let (users, kept) = split_retry(&records);
+        let (user_ids, mut fails) = classify(&users, &mut HashSet::new());
+        let issued = issue(&txn, task.coupon_template_id, user_ids, &mut fails).await?;
+        let ids: Vec<i64> = records.iter().map(|record| record.id).collect();
+        coupon_task_fail_dao().delete_by_ids(&txn, &ids).await?;
+        let new_records: Vec<coupon_task_fail::Model> = kept
+            .into_iter()
+            .chain(
+                fails
+                    .iter()
+                    .map(|fail| serde_json::to_string(fail).unwrap_or_default()),
+            )
+            .map(|json_object| coupon_task_fail::Model {
+                id: 0,
+                batch_id: task.batch_id,
+                json_object,
+            })
+            .collect();
+        coupon_task_fail_dao()
+            .create_batch(&txn, &new_records)
+            .await?;
+        coupon_task_dao()
+            .advance(
+                &txn,
+                task.id,
+                0,
+                issued.len() as i32,
+                new_records.len() as i32 - records.len() as i32,
+            )
+            .await?;
+        txn.commit().await?;
+        coupon_task_progress::publish(task.id);
+        notify::dispatch(&current, issued, app_state.clone());
+    }
+}
+
+/// 将失败记录分为需要重新发放的用户和原样保留的记录内容
+///
+/// 用户ID无效和重复的用户重试也不会成功，无法解析的记录同样保留
+fn split_retry(records: &[coupon_task_fail::Model]) -> (Vec<String>, Vec<String>) {
+    let mut users = Vec::new();
+    let mut kept = Vec::new();
+    for record in records {
+        match serde_json::from_str::<TaskFailRecord>(&record.json_object) {
+            Ok(fail)
+                if fail.reason != REASON_INVALID_USER && fail.reason != REASON_DUPLICATE_USER =>
+            {
+                users.push(fail.user_id)
+            }
+            _ => kept.push(record.json_object.clone()),
+        }
+    }
+    (users, kept)
+}
+
+/// 将一批用户分为有效用户ID和发放失败的用户，`seen` 中已有的用户ID视为重复
+fn classify(users: &[String], seen: &mut HashSet<i64>) -> (Vec<i64>, Vec<TaskFailRecord>) {
+    let mut user_ids = Vec::with_capacity(users.len());
+    let mut fails = Vec::new();
+    for user in users {
+        match user.parse::<i64>() {
+            Ok(user_id) if user_id > 0 => {
+                if seen.insert(user_id) {
+                    user_ids.push(user_id);
+                } else {
+                    fails.push(TaskFailRecord::new(user, REASON_DUPLICATE_USER));
+                }
+            }
+            _ => fails.push(TaskFailRecord::new(user, REASON_INVALID_USER)),
+        }
+    }
+    (user_ids, fails)
+}
+
+/// 在事务中向用户发放优惠券，返回发放成功的用户ID；无法发放的用户追加到 `fails`
+///
+/// 模板行加锁后按剩余库存发放，库存不足的用户记为失败
+async fn issue(
+    txn: &DatabaseTransaction,
+    coupon_template_id: i64,
+    user_ids: Vec<i64>,
+    fails: &mut Vec<TaskFailRecord>,
+) -> Result<Vec<i64>, AppError> {
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let template = template_dao()
+        .find_by_id_for_update(txn, coupon_template_id)
+        .await?
+        .filter(|template| {
+            template.is_issuable() && template.valid_end_time.is_none_or(|end| end > Utc::now())
+        });
+    let Some(template) = template else {
+        fails.extend(
+            user_ids
+                .iter()
+                .map(|user_id| TaskFailRecord::new(user_id, REASON_NOT_ISSUABLE)),
+        );
+        return Ok(Vec::new());
+    };
+
+    let available = usize::try_from(template.stock).unwrap_or(0);
+    let (issued, out_of_stock) = user_ids.split_at(user_ids.len().min(available));
+    fails.extend(
+        out_of_stock
+            .iter()
+            .map(|user_id| TaskFailRecord::new(user_id, REASON_OUT_OF_STOCK)),
+    );
+    if issued.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows = template_dao()
+        .decrease_stock(txn, &template, issued.len() as i32)
+        .await?;
+    if rows == 0 {
+        // 模板已加锁，扣减失败说明库存或状态已不满足发放条件，这批用户都不发放
+        fails.extend(
+            issued
+                .iter()
+                .map(|user_id| TaskFailRecord::new(user_id, REASON_OUT_OF_STOCK)),
+        );
+        return Ok(Vec::new());
+    }
+
+    let receive_counts: HashMap<i64, i32> = user_coupon_dao()
+        .max_receive_counts(txn, template.id, issued)
+        .await?
+        .into_iter()
+        .collect();
+    let coupons: Vec<user_coupon::Model> = issued
+        .iter()
+        .map(|user_id| {
+            let receive_count = receive_counts.get(user_id).copied().unwrap_or(0) + 1;
+            new_user_coupon(&template, *user_id, receive_count)
+        })
+        .collect();
+    user_coupon_dao().create_batch(txn, &coupons).await?;
+    Ok(issued.to_vec())
+}
+
+/// 全部用户处理完成后结束任务：没有失败用户时为执行成功，否则为执行失败
+async fn finish(task_id: i64, app_state: &Data<AppState>) -> Result<(), AppError> {
+    let txn = app_state.database.begin().await?;
+    let Some(task) = coupon_task_dao()
+        .find_by_id_for_update(&txn, task_id)
+        .await?
+    else {
+        return Ok(());
+    };
+    let status = if task.fail_num == 0 {
+        TaskStatus::Succeeded
+    } else {
+        TaskStatus::Failed
+    };
+    coupon_task_dao()
+        .transit(&txn, task_id, TaskStatus::Running, status.clone())
+        .await?;
+    txn.commit().await?;
+    coupon_task_progress::publish(task_id);
+
+    info!(
+        "优惠券分发任务{}, 任务ID: {}, 成功: {}, 失败: {}",
+        status.label(),
+        task_id,
+        task.success_num,
+        task.fail_num
+    );
+    // 失败用户文件生成失败不影响任务结果，下载时会重新生成
+    if task.fail_num > 0 {
+        if let Err(err) = coupon_task_fail_file::generate(&task, app_state).await {
+            error!("生成失败用户文件失败, 任务ID: {}, 错误: {}", task_id, err);
+        }
+    }
+    Ok(())
+}
+
+/// 平台发放的用户优惠券，有效期与模板一致
+fn new_user_coupon(
+    template: &template::Model,
+    user_id: i64,
+    receive_count: i32,
+) -> user_coupon::Model {
+    let now = Utc::now();
+    user_coupon::Model {
+        id: 0,
+        user_id,
+        coupon_template_id: template.id,
+        receive_time: Some(now),
+        receive_count,
+        valid_start_time: template.valid_start_time,
+        valid_end_time: template.valid_end_time,
+        use_time: None,
+        source: UserCouponSource::PlatformDistribution,
+        status: UserCouponStatus::Unused,
+        create_time: Some(now),
+        update_time: Some(now),
+        del_flag: NOT_DELETED,
+    }
+}
+
+static COUPON_TASK_EXECUTOR: Lazy<CouponTaskExecutorImpl> = Lazy::new(|| CouponTaskExecutorImpl);
+
+pub fn coupon_task_executor() -> &'static dyn CouponTaskExecutor {
+    &*COUPON_TASK_EXECUTOR
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn classifies_invalid_and_duplicate_users() {
+        let mut seen = HashSet::from([1001]);
+        let (user_ids, fails) = classify(&users(&["1001", "1002", "abc", "-1", "1002"]), &mut seen);
+
+        assert_eq!(user_ids, vec![1002]);
+        assert_eq!(
+            fails,
+            vec![
+                TaskFailRecord::new("1001", REASON_DUPLICATE_USER),
+                TaskFailRecord::new("abc", REASON_INVALID_USER),
+                TaskFailRecord::new("-1", REASON_INVALID_USER),
+                TaskFailRecord::new("1002", REASON_DUPLICATE_USER),
+            ]
+        );
+    }
+
+    #[test]
+    fn retries_only_recoverable_failures() {
+        let records: Vec<coupon_task_fail::Model> = [
+            serde_json::to_string(&TaskFailRecord::new("1001", REASON_OUT_OF_STOCK)).unwrap(),
+            serde_json::to_string(&TaskFailRecord::new("abc", REASON_INVALID_USER)).unwrap(),
+            serde_json::to_string(&TaskFailRecord::new("1001", REASON_DUPLICATE_USER)).unwrap(),
+            serde_json::to_string(&TaskFailRecord::new("1002", REASON_NOT_ISSUABLE)).unwrap(),
+            "not json".to_string(),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, json_object)| coupon_task_fail::Model {
+            id: index as i64 + 1,
+            batch_id: 1,
+            json_object,
+        })
+        .collect();
+
+        let (users, kept) = split_retry(&records);
+        assert_eq!(users, vec!["1001", "1002"]);
+        assert_eq!(
+            kept,
+            vec![
+                records[1].json_object.clone(),
+                records[2].json_object.clone(),
+                records[4].json_object.clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn resumes_from_processed_users() {
+        let users = users(&["1001", "1002", "abc", "1001", "1003", "1002", "1004"]);
+        let run = |resume_at: usize| {
+            let mut seen = HashSet::new();
+            let mut results = Vec::new();
+            let (mut scanned, mut offset) = (0, resume_at);
+            while offset < users.len() {
+                let (end, user_ids, fails) = next_chunk(&users, scanned, offset, 2, &mut seen);
+                results.push((user_ids, fails));
+                scanned = end;
+                offset = end;
+            }
+            results
+        };
+
+        let full = run(0);
+        assert_eq!(full.last().unwrap().0, vec![1004]);
+        // 在第二批之后重启，剩余批次的结果与不中断时一致，与已处理部分重复的用户仍被识别
+        assert_eq!(run(4), full[2..]);
+        assert_eq!(
+            run(4)[0],
+            (
+                vec![1003],
+                vec![TaskFailRecord::new("1002", REASON_DUPLICATE_USER)]
+            )
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::coupon_task::TASK_FILE_DIR;
+use crate::coupon_task_executor::TaskFailRecord;
+use crate::template_export::ExportFormat;
+use crate::template_import::is_xlsx;
+use crate::AppState;
+use actix_web::web::{self, Data};
+use common::app_error::AppError;
+use data::dao::coupon_task::coupon_task_dao;
+use data::dao::coupon_task_fail::coupon_task_fail_dao;
+use data::entity::{coupon_task, coupon_task_fail};
+use log::{error, info};
+use rust_xlsxwriter::{Workbook, XlsxError};
+use sea_orm::TransactionTrait;
+
+/// 每批从数据库读取的失败记录数
+const BATCH_SIZE: u64 = 1000;
+/// CSV 文件开头的 UTF-8 BOM，保证 Excel 打开时中文不乱码
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+/// 失败用户文件的表头，第一列为用户ID，修改后可以直接作为用户文件重新上传
+const HEADERS: [&str; 2] = ["用户ID", "失败原因"];
+
+/// 可下载的任务文件
+pub struct TaskFile {
+    pub file_name: String,
+    pub content_type: &'static str,
+    pub content: Vec<u8>,
+}
+
+/// 失败用户文件的格式与上传的用户文件一致
+pub(crate) fn format_of(task: &coupon_task::Model) -> ExportFormat {
+    if is_xlsx(Some(&task.file_address), &[]) {
+        ExportFormat::Xlsx
+    } else {
+        ExportFormat::Csv
+    }
+}
+
+/// 下载时使用的失败用户文件名
+pub(crate) fn download_name(task: &coupon_task::Model) -> String {
+    format!(
+        "coupon-task-{}-fail.{}",
+        task.batch_id,
+        format_of(task).extension()
+    )
+}
+
+/// 汇总任务批次的发放失败记录生成失败用户文件，保存到文件存储并记录到任务上，返回文件地址
+pub(crate) async fn generate(
+    task: &coupon_task::Model,
+    app_state: &Data<AppState>,
+) -> Result<String, AppError> {
+    let mut records = Vec::new();
+    let mut after_id = 0;
+    loop {
+        let batch = coupon_task_fail_dao()
+            .list_after(&app_state.database, task.batch_id, after_id, BATCH_SIZE)
+            .await?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        after_id = last.id;
+        let finished = (batch.len() as u64) < BATCH_SIZE;
+        records.extend(batch.iter().map(to_record));
+        if finished {
+            break;
+        }
+    }
+
+    let format = format_of(task);
+    let content = match format {
+        ExportFormat::Csv => csv_content(&records),
+        ExportFormat::Xlsx => web::block(move || xlsx_content(&records))
+            .await
+            .map_err(AppError::internal_error)?,
+    }?;
+    let key = format!(
+        "{}/{}-fail.{}",
+        TASK_FILE_DIR,
+        task.batch_id,
+        format.extension()
+    );
+    let address = app_state.storage.upload(&key, content).await?;
+    let txn = app_state.database.begin().await?;
+    coupon_task_dao()
+        .update_fail_file_address(&txn, task.id, Some(address.clone()))
+        .await?;
+    txn.commit().await?;
+
+    info!(
+        "生成优惠券分发任务失败用户文件, 任务ID: {}, 文件: {}",
+        task.id, address
+    );
+    Ok(address)
+}
+
+/// 解析失败记录，内容无法解析时保留原文
+fn to_record(model: &coupon_task_fail::Model) -> TaskFailRecord {
+    serde_json::from_str(&model.json_object).unwrap_or_else(|err| {
+        error!("发放失败记录格式错误, 记录ID: {}, 错误: {}", model.id, err);
+        TaskFailRecord {
+            user_id: String::new(),
+            reason: model.json_object.clone(),
+        }
+    })
+}
+
+fn csv_content(records: &[TaskFailRecord]) -> Result<Vec<u8>, AppError> {
+    let mut writer = csv::Writer::from_writer(UTF8_BOM.to_vec());
+    writer.write_record(HEADERS).map_err(csv_error)?;
+    for record in records {
+        writer
+            .write_record([&record.user_id, &record.reason])
+            .map_err(csv_error)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|err| csv_error(err.into_error()))
+}
+
+fn xlsx_content(records: &[TaskFailRecord]) -> Result<Vec<u8>, AppError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("发放失败用户").map_err(xlsx_error)?;
+    for (col, header) in HEADERS.iter().enumerate() {
+        worksheet
+            .write_string(0, col as u16, *header)
+            .map_err(xlsx_error)?;
+    }
+    for (index, record) in records.iter().enumerate() {
+        let row = index as u32 + 1;
+        worksheet
+            .write_string(row, 0, &record.user_id)
+            .map_err(xlsx_error)?;
+        worksheet
+            .write_string(row, 1, &record.reason)
+            .map_err(xlsx_error)?;
+    }
+    workbook.save_to_buffer().map_err(xlsx_error)
+}
+
+fn csv_error(err: impl std::fmt::Display) -> AppError {
+    error!("生成失败用户 CSV 文件失败: {}", err);
+    AppError::internal_error(format!("生成失败用户文件失败: {}", err))
+}
+
+fn xlsx_error(err: XlsxError) -> AppError {
+    error!("生成失败用户 Excel 文件失败: {}", err);
+    AppError::internal_error(format!("生成失败用户文件失败: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coupon_task::parse_users;
+
+    #[test]
+    fn fail_file_can_be_uploaded_again() {
+        let records = vec![
+            TaskFailRecord {
+                user_id: "1001".to_string(),
+                reason: "优惠券模板库存不足".to_string(),
+            },
+            TaskFailRecord {
+                user_id: "abc".to_string(),
+                reason: "用户ID无效".to_string(),
+            },
+        ];
+
+        let csv = csv_content(&records).unwrap();
+        assert_eq!(
+            parse_users(Some("fail.csv"), &csv).unwrap(),
+            vec!["1001", "abc"]
+        );
+        let xlsx = xlsx_content(&records).unwrap();
+        assert_eq!(
+            parse_users(Some("fail.xlsx"), &xlsx).unwrap(),
+            vec!["1001", "abc"]
+        );
+    }
+}
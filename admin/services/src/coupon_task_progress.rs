@@ -0,0 +1,162 @@
+use crate::AppState;
+use actix_web::web::Data;
+use chrono::Utc;
+use data::dao::coupon_task::coupon_task_dao;
+use data::entity::coupon_task;
+use data::enums::TaskStatus;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use log::error;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+/// 进度变化通知的缓冲数量，订阅方落后超过该数量时直接重新查询进度
+const EVENT_CAPACITY: usize = 1024;
+/// 没有收到通知时重新查询进度的间隔，其他实例执行的任务依赖它更新进度
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// 进度没有变化时推送心跳的间隔
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// 每个进度流的最长持续时间
+const MAX_STREAM_DURATION: Duration = Duration::from_secs(30 * 60);
+
+/// 分发任务进度变化通知，只携带任务ID，订阅方收到后重新查询进度
+static PROGRESS_EVENTS: Lazy<broadcast::Sender<i64>> =
+    Lazy::new(|| broadcast::channel(EVENT_CAPACITY).0);
+
+/// 分发任务执行进度
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskProgress {
+    /// 分发任务ID
+    pub coupon_task_id: i64,
+    /// 任务状态
+    pub status: TaskStatus,
+    /// 用户总数，即发放数量
+    pub total: i32,
+    /// 已处理用户数量
+    pub processed: i32,
+    /// 发放成功数量
+    pub succeeded: i32,
+    /// 发放失败数量
+    pub failed: i32,
+}
+
+impl From<&coupon_task::Model> for TaskProgress {
+    fn from(task: &coupon_task::Model) -> Self {
+        TaskProgress {
+            coupon_task_id: task.id,
+            status: task.status.clone(),
+            total: task.send_num,
+            processed: task.processed_num,
+            succeeded: task.success_num,
+            failed: task.fail_num,
+        }
+    }
+}
+
+/// 通知任务进度或状态已变化，在修改任务的事务提交后调用
+pub(crate) fn publish(task_id: i64) {
+    // 没有订阅方时发送失败，忽略即可
+    let _ = PROGRESS_EVENTS.send(task_id);
+}
+
+/// 推送给订阅方的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// 任务进度
+    Progress(TaskProgress),
+    /// 进度长时间没有变化时的心跳，避免连接被代理或客户端当作空闲断开
+    Heartbeat,
+}
+
+/// 订阅进度的流的状态
+struct WatchState {
+    receiver: broadcast::Receiver<i64>,
+    /// 最近推送的进度
+    last: Option<TaskProgress>,
+    /// 最近推送事件的时间
+    last_sent: Instant,
+    /// 流的结束时间
+    deadline: Instant,
+}
+
+/// 持续推送任务进度，首先推送当前进度，之后只在进度变化时推送，任务结束后推送最终进度并结束
+///
+/// 本实例执行的任务收到通知后立即推送，其他实例执行的任务按轮询间隔推送；进度没有变化时按心跳间隔推送心跳。
+/// 尚未到发送时间的定时任务在开始执行前进度不会变化，推送当前进度后直接结束；
+/// 每个流最多持续 `MAX_STREAM_DURATION`，之后由客户端重新连接
+pub(crate) fn watch(task_id: i64, app_state: Data<AppState>) -> BoxStream<'static, ProgressEvent> {
+    // 先订阅再查询，避免遗漏查询期间的变化
+    let now = Instant::now();
+    let state = WatchState {
+        receiver: PROGRESS_EVENTS.subscribe(),
+        last: None,
+        last_sent: now,
+        deadline: now + MAX_STREAM_DURATION,
+    };
+    stream::unfold(Some(state), move |state| {
+        let app_state = app_state.clone();
+        async move {
+            let mut state = state?;
+            loop {
+                if state.last.is_some() {
+                    if Instant::now() >= state.deadline {
+                        return None;
+                    }
+                    wait_for_change(&mut state.receiver, task_id).await;
+                }
+                let task = match coupon_task_dao()
+                    .find_by_id(&app_state.database, task_id)
+                    .await
+                {
+                    Ok(Some(task)) => task,
+                    Ok(None) => return None,
+                    Err(err) => {
+                        error!(
+                            "查询优惠券分发任务进度失败, 任务ID: {}, 错误: {}",
+                            task_id, err
+                        );
+                        return None;
+                    }
+                };
+                let progress = TaskProgress::from(&task);
+                if state.last.as_ref() == Some(&progress) {
+                    if state.last_sent.elapsed() < HEARTBEAT_INTERVAL {
+                        continue;
+                    }
+                    state.last_sent = Instant::now();
+                    return Some((ProgressEvent::Heartbeat, Some(state)));
+                }
+
+                let finished = progress.status.is_terminal() || is_waiting(&task);
+                state.last = Some(progress.clone());
+                state.last_sent = Instant::now();
+                let next = if finished { None } else { Some(state) };
+                return Some((ProgressEvent::Progress(progress), next));
+            }
+        }
+    })
+    .boxed()
+}
+
+/// 任务是否仍在等待发送时间
+fn is_waiting(task: &coupon_task::Model) -> bool {
+    task.status == TaskStatus::Pending && task.send_time.is_some_and(|time| time > Utc::now())
+}
+
+/// 等待任务的变化通知，最多等待一个轮询间隔
+async fn wait_for_change(receiver: &mut broadcast::Receiver<i64>, task_id: i64) {
+    let _ = tokio::time::timeout(POLL_INTERVAL, async {
+        loop {
+            match receiver.recv().await {
+                Ok(id) if id == task_id => break,
+                Ok(_) => continue,
+                // 落后时不知道是否遗漏了该任务的通知，直接重新查询
+                Err(_) => break,
+            }
+        }
+    })
+    .await;
+}
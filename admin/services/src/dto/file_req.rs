@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// 临时下载链接请求 DTO，参数由文件存储生成链接时签名
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileDownloadReqDto {
+    /// 文件地址
+    pub key: String,
+
+    /// 过期时间 (Unix 秒)
+    pub expires: i64,
+
+    /// 签名
+    pub signature: String,
+}
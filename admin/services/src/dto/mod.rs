@@ -1 +1,5 @@
-pub mod template_req;
\ No newline at end of file
+pub mod file_req;
+pub mod page_req;
+pub mod task_req;
+pub mod template_req;
+pub mod user_coupon_req;
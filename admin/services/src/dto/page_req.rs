@@ -0,0 +1,44 @@
+use common::app_error::AppError;
+use common::error_code::BaseErrorCode;
+use serde::{Deserialize, Serialize};
+
+/// 分页查询时单页允许的最大记录数
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// 通用分页请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageReqDto {
+    /// 当前页码，从 1 开始
+    #[serde(default = "default_current")]
+    pub current: u64,
+
+    /// 每页记录数
+    #[serde(default = "default_size")]
+    pub size: u64,
+}
+
+pub(crate) fn default_current() -> u64 {
+    1
+}
+
+pub(crate) fn default_size() -> u64 {
+    10
+}
+
+/// 校验分页参数，单页数量超过上限时返回 `SearchAmountExceedsLimit`
+pub(crate) fn check_page(current: u64, size: u64) -> Result<(), AppError> {
+    if size > MAX_PAGE_SIZE {
+        return Err(AppError::client(
+            BaseErrorCode::SearchAmountExceedsLimit,
+            Some(format!("每页最多查询 {} 条记录", MAX_PAGE_SIZE)),
+        ));
+    }
+    if current == 0 || size == 0 {
+        return Err(AppError::client(
+            BaseErrorCode::InvalidParam,
+            Some("页码和每页记录数必须大于0".to_string()),
+        ));
+    }
+    Ok(())
+}
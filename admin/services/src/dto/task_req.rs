@@ -0,0 +1,136 @@
+use crate::dto::page_req::{default_current, default_size};
+use crate::validation::{Validate, Validator};
+use chrono::{DateTime, Utc};
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use data::dao::coupon_task::TaskFilter;
+use data::enums::{TaskSendType, TaskStatus};
+use serde::{Deserialize, Serialize};
+
+/// 通知方式的取值范围：0：站内信 1：弹框推送 2：邮箱 3：短信
+const NOTIFY_TYPES: [&str; 4] = ["0", "1", "2", "3"];
+
+/// 分发任务创建请求 DTO，用户文件通过表单的 `file` 字段单独上传
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCreateReqDto {
+    /// 任务名称
+    pub task_name: String,
+
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+
+    /// 发送类型
+    /// 示例: 0 (立即发送)
+    #[serde(default)]
+    pub send_type: TaskSendType,
+
+    /// 发送时间，定时发送时必填 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string", default)]
+    pub send_time: Option<DateTime<Utc>>,
+
+    /// 通知方式，多个以逗号分隔
+    /// 示例: "0,3" (站内信和短信)
+    #[serde(default)]
+    pub notify_type: Option<String>,
+}
+
+impl Validate for TaskCreateReqDto {
+    fn rules(&self, v: &mut Validator) {
+        v.not_blank("taskName", &self.task_name)
+            .max_chars("taskName", &self.task_name, 128);
+
+        if self.send_type == TaskSendType::Scheduled {
+            v.required("sendTime", &self.send_time);
+            if let Some(send_time) = self.send_time {
+                v.check(
+                    "sendTime",
+                    send_time > Utc::now(),
+                    "发送时间必须晚于当前时间",
+                );
+            }
+        }
+        if let Some(notify_type) = &self.notify_type {
+            let types: Vec<&str> = notify_type.split(',').map(str::trim).collect();
+            v.check(
+                "notifyType",
+                types.iter().all(|value| NOTIFY_TYPES.contains(value)),
+                "通知方式只能为 0、1、2、3，多个以逗号分隔",
+            );
+            v.check(
+                "notifyType",
+                types
+                    .iter()
+                    .enumerate()
+                    .all(|(i, value)| !types[..i].contains(value)),
+                "通知方式不能重复",
+            );
+        }
+    }
+}
+
+/// 按 ID 操作分发任务的请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskIdReqDto {
+    /// 分发任务ID
+    pub coupon_task_id: i64,
+}
+
+/// 修改定时分发任务发送时间的请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRescheduleReqDto {
+    /// 分发任务ID
+    pub coupon_task_id: i64,
+
+    /// 新的发送时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string", default)]
+    pub send_time: Option<DateTime<Utc>>,
+}
+
+impl Validate for TaskRescheduleReqDto {
+    fn rules(&self, v: &mut Validator) {
+        v.required("sendTime", &self.send_time);
+        if let Some(send_time) = self.send_time {
+            v.check(
+                "sendTime",
+                send_time > Utc::now(),
+                "发送时间必须晚于当前时间",
+            );
+        }
+    }
+}
+
+/// 分发任务分页查询请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskPageQueryReqDto {
+    /// 当前页码，从 1 开始
+    #[serde(default = "default_current")]
+    pub current: u64,
+
+    /// 每页记录数
+    #[serde(default = "default_size")]
+    pub size: u64,
+
+    /// 任务名称，模糊匹配
+    pub task_name: Option<String>,
+
+    /// 优惠券模板ID
+    pub coupon_template_id: Option<i64>,
+
+    /// 任务状态
+    pub status: Option<TaskStatus>,
+}
+
+impl TaskPageQueryReqDto {
+    /// 转换为限定在指定店铺内的 DAO 查询条件
+    pub fn to_filter(&self, shop_number: i64) -> TaskFilter {
+        TaskFilter {
+            shop_number,
+            task_name: self.task_name.clone(),
+            coupon_template_id: self.coupon_template_id,
+            status: self.status.clone(),
+        }
+    }
+}
@@ -1,9 +1,15 @@
 use crate::auth::SHOP_NUMBER;
+use crate::dto::page_req::{default_current, default_size};
+use crate::template_export::ExportFormat;
+use crate::validation::{Validate, Validator};
 use chrono::{DateTime, Utc};
 use common::app_error::AppError;
 use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use data::dao::template::TemplateFilter;
 use data::entity::template;
-use data::enums::{CouponSource, CouponStatus, CouponTarget, CouponType};
+use data::enums::{AuditStatus, CouponSource, CouponStatus, CouponTarget, CouponType};
+use data::rule::{ConsumeRule, ReceiveRule};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 // 移除了 From, 添加了 TryFrom
@@ -56,6 +62,62 @@ pub struct TemplateSaveReqDto {
     pub consume_rule: String,
 }
 
+impl Validate for TemplateSaveReqDto {
+    fn rules(&self, v: &mut Validator) {
+        v.not_blank("name", &self.name)
+            .max_chars("name", &self.name, 256)
+            .positive("stock", self.stock.into());
+
+        target_rules(v, &self.target, &self.goods);
+        validity_rules(v, self.valid_start_time, self.valid_end_time);
+        coupon_rules(v, &self.r#type, &self.receive_rule, &self.consume_rule);
+    }
+}
+
+/// 优惠对象与商品编码的校验规则
+fn target_rules(v: &mut Validator, target: &CouponTarget, goods: &str) {
+    v.max_chars("goods", goods, 64);
+    if *target == CouponTarget::SpecificGoods {
+        v.check("goods", !goods.trim().is_empty(), "商品专属券必须指定商品");
+    }
+}
+
+/// 有效期的校验规则
+fn validity_rules(
+    v: &mut Validator,
+    valid_start_time: Option<DateTime<Utc>>,
+    valid_end_time: Option<DateTime<Utc>>,
+) {
+    v.required("validStartTime", &valid_start_time)
+        .required("validEndTime", &valid_end_time);
+    if let (Some(start), Some(end)) = (valid_start_time, valid_end_time) {
+        v.check(
+            "validEndTime",
+            end > start,
+            "有效期结束时间必须晚于开始时间",
+        );
+    }
+    if let Some(end) = valid_end_time {
+        v.check(
+            "validEndTime",
+            end > Utc::now(),
+            "有效期结束时间不能早于当前时间",
+        );
+    }
+}
+
+/// 领取规则和消耗规则的校验规则
+fn coupon_rules(v: &mut Validator, r#type: &CouponType, receive_rule: &str, consume_rule: &str) {
+    match ReceiveRule::parse(receive_rule) {
+        Ok(rule) => v.violations(rule.violations()),
+        Err(violation) => v.violations(vec![violation]),
+    };
+    match ConsumeRule::parse(consume_rule) {
+        Ok(rule) => v.violations(rule.violations(r#type)),
+        Err(violation) => v.violations(vec![violation]),
+    };
+}
+
 // 实现从 TemplateSaveReqDto 到 template::Model 的转换 (使用 TryFrom)
 impl TryFrom<TemplateSaveReqDto> for template::Model {
     type Error = AppError; // 指定错误类型
@@ -78,6 +140,14 @@ impl TryFrom<TemplateSaveReqDto> for template::Model {
             }
         };
 
+        // 严格解析领取规则和消耗规则，并按优惠类型校验
+        let receive_rule = ReceiveRule::parse(&dto.receive_rule).map_err(|v| v.into_error())?;
+        receive_rule.validate()?;
+        let consume_rule = ConsumeRule::parse(&dto.consume_rule).map_err(|v| v.into_error())?;
+        consume_rule.validate(&dto.r#type)?;
+
+        let audit_status = AuditStatus::initial(&dto.source);
+
         Ok(template::Model {
             // id 是主键，设置为默认值，由数据库生成
             id: 0,
@@ -97,15 +167,20 @@ impl TryFrom<TemplateSaveReqDto> for template::Model {
             // model.valid_end_time 是 Option<DateTime<Utc>>
             valid_end_time: Some(checked_valid_end_time),
 
-            // JSON 字符串字段转换为 Option<JsonValue>
-            receive_rule: serde_json::from_str(&dto.receive_rule).ok(),
-            consume_rule: serde_json::from_str(&dto.consume_rule).ok(),
+            // 校验通过的规则仍以 JSON 形式存储
+            receive_rule: Some(receive_rule.to_json()),
+            consume_rule: Some(consume_rule.to_json()),
 
             // 模型中由系统设置的字段
             status: CouponStatus::Active,
             create_time: Some(Utc::now()),
             update_time: Some(Utc::now()),
             del_flag: 0,
+            delete_time: None,
+            audit_status,
+            reviewer_id: None,
+            audit_remark: None,
+            audit_time: None,
         })
     }
 }
@@ -120,3 +195,409 @@ impl TryFrom<&TemplateSaveReqDto> for template::Model {
         Self::try_from(dto.clone())
     }
 }
+
+/// 优惠券模板修改请求 DTO
+///
+/// 库存通过增加库存接口单独调整，来源和店铺不允许修改
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateUpdateReqDto {
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+
+    /// 优惠券名称
+    pub name: String,
+
+    /// 优惠对象
+    pub target: CouponTarget,
+
+    /// 优惠商品编码 (如果 target 是商品专属)
+    pub goods: String,
+
+    /// 优惠类型
+    #[serde(rename = "type")]
+    pub r#type: CouponType,
+
+    /// 有效期开始时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub valid_start_time: Option<DateTime<Utc>>,
+
+    /// 有效期结束时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub valid_end_time: Option<DateTime<Utc>>,
+
+    /// 领取规则 (JSON 字符串)
+    pub receive_rule: String,
+
+    /// 消耗规则 (JSON 字符串)
+    pub consume_rule: String,
+
+    /// 查询模板详情时返回的修改时间，作为乐观锁版本号
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string", default)]
+    pub update_time: Option<DateTime<Utc>>,
+}
+
+impl Validate for TemplateUpdateReqDto {
+    fn rules(&self, v: &mut Validator) {
+        v.not_blank("name", &self.name)
+            .max_chars("name", &self.name, 256);
+
+        target_rules(v, &self.target, &self.goods);
+        validity_rules(v, self.valid_start_time, self.valid_end_time);
+        coupon_rules(v, &self.r#type, &self.receive_rule, &self.consume_rule);
+    }
+}
+
+impl TemplateUpdateReqDto {
+    /// 将修改内容应用到原模板上，返回修改后的模板，修改时间由调用方设置
+    pub fn apply_to(&self, original: &template::Model) -> Result<template::Model, AppError> {
+        let receive_rule = ReceiveRule::parse(&self.receive_rule).map_err(|v| v.into_error())?;
+        let consume_rule = ConsumeRule::parse(&self.consume_rule).map_err(|v| v.into_error())?;
+
+        Ok(template::Model {
+            name: self.name.clone(),
+            target: self.target.clone(),
+            goods: self.goods.clone(),
+            r#type: self.r#type.clone(),
+            valid_start_time: self.valid_start_time,
+            valid_end_time: self.valid_end_time,
+            receive_rule: Some(receive_rule.to_json()),
+            consume_rule: Some(consume_rule.to_json()),
+            ..original.clone()
+        })
+    }
+}
+
+/// 复制优惠券模板请求 DTO
+///
+/// 未填写的字段沿用源模板的值
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateCopyReqDto {
+    /// 新模板名称
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// 有效期开始时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string", default)]
+    pub valid_start_time: Option<DateTime<Utc>>,
+
+    /// 有效期结束时间 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string", default)]
+    pub valid_end_time: Option<DateTime<Utc>>,
+
+    /// 库存
+    #[serde(default)]
+    pub stock: Option<i32>,
+}
+
+impl TemplateCopyReqDto {
+    /// 以源模板为基础生成新模板，新模板重新生效并重新审核，ID 由数据库生成
+    ///
+    /// 覆盖后的名称、库存和有效期需要重新校验，例如源模板已过期时必须指定新的有效期
+    pub fn copy_from(&self, source: &template::Model) -> Result<template::Model, AppError> {
+        let now = Utc::now();
+        let copied = template::Model {
+            id: 0,
+            name: self.name.clone().unwrap_or_else(|| source.name.clone()),
+            valid_start_time: self.valid_start_time.or(source.valid_start_time),
+            valid_end_time: self.valid_end_time.or(source.valid_end_time),
+            stock: self.stock.unwrap_or(source.stock),
+            status: CouponStatus::Active,
+            create_time: Some(now),
+            update_time: Some(now),
+            del_flag: 0,
+            delete_time: None,
+            audit_status: AuditStatus::initial(&source.source),
+            reviewer_id: None,
+            audit_remark: None,
+            audit_time: None,
+            ..source.clone()
+        };
+
+        let mut v = Validator::new();
+        v.not_blank("name", &copied.name)
+            .max_chars("name", &copied.name, 256)
+            .positive("stock", copied.stock.into());
+        validity_rules(&mut v, copied.valid_start_time, copied.valid_end_time);
+        v.finish()?;
+
+        Ok(copied)
+    }
+}
+
+/// 按 ID 操作优惠券模板的请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateIdReqDto {
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+}
+
+/// 审核通过优惠券模板请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateApproveReqDto {
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+
+    /// 审核人ID
+    pub reviewer_id: i64,
+
+    /// 审核意见
+    #[serde(default)]
+    pub remark: Option<String>,
+}
+
+impl Validate for TemplateApproveReqDto {
+    fn rules(&self, v: &mut Validator) {
+        v.positive("reviewerId", self.reviewer_id);
+        if let Some(remark) = &self.remark {
+            v.max_chars("remark", remark, 256);
+        }
+    }
+}
+
+/// 驳回优惠券模板请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateRejectReqDto {
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+
+    /// 审核人ID
+    pub reviewer_id: i64,
+
+    /// 驳回原因
+    pub reason: String,
+}
+
+impl Validate for TemplateRejectReqDto {
+    fn rules(&self, v: &mut Validator) {
+        v.positive("reviewerId", self.reviewer_id)
+            .not_blank("reason", &self.reason)
+            .max_chars("reason", &self.reason, 256);
+    }
+}
+
+/// 优惠券模板增加库存请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateNumberReqDto {
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+
+    /// 增加的库存数量
+    /// 示例: 100
+    pub number: i32,
+}
+
+/// 优惠券模板分页查询请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePageQueryReqDto {
+    /// 当前页码，从 1 开始
+    #[serde(default = "default_current")]
+    pub current: u64,
+
+    /// 每页记录数
+    #[serde(default = "default_size")]
+    pub size: u64,
+
+    /// 优惠券名称，模糊匹配
+    pub name: Option<String>,
+
+    /// 优惠类型
+    #[serde(rename = "type")]
+    pub r#type: Option<CouponType>,
+
+    /// 优惠对象
+    pub target: Option<CouponTarget>,
+
+    /// 优惠券状态
+    pub status: Option<CouponStatus>,
+
+    /// 审核状态
+    pub audit_status: Option<AuditStatus>,
+
+    /// 优惠商品编码
+    pub goods: Option<String>,
+
+    /// 有效期开始时间下限 (GMT+8 字符串)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string", default)]
+    pub valid_start_time: Option<DateTime<Utc>>,
+
+    /// 有效期结束时间上限 (GMT+8 字符串)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string", default)]
+    pub valid_end_time: Option<DateTime<Utc>>,
+}
+
+impl TemplatePageQueryReqDto {
+    /// 转换为限定在指定店铺内的 DAO 查询条件
+    pub fn to_filter(&self, shop_number: i64) -> TemplateFilter {
+        TemplateFilter {
+            shop_number,
+            name: self.name.clone(),
+            r#type: self.r#type.clone(),
+            target: self.target.clone(),
+            status: self.status.clone(),
+            audit_status: self.audit_status.clone(),
+            goods: self.goods.clone(),
+            valid_start_time: self.valid_start_time,
+            valid_end_time: self.valid_end_time,
+        }
+    }
+}
+
+/// 优惠券模板导出请求 DTO，查询条件与分页查询相同
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateExportReqDto {
+    /// 导出文件格式：csv 或 xlsx，为空时根据 `Accept` 请求头选择
+    pub format: Option<ExportFormat>,
+}
+
+/// 优惠券模板导入请求 DTO
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateImportReqDto {
+    /// 是否全部成功才导入：为 true 时任意一行校验不通过都不会写入任何数据
+    #[serde(default)]
+    pub all_or_nothing: bool,
+}
+
+/// 优惠试算请求 DTO
+///
+/// 模板ID和未保存的模板内容必须且只能填写一个
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePreviewReqDto {
+    /// 已保存的优惠券模板ID
+    pub coupon_template_id: Option<i64>,
+
+    /// 未保存的优惠券模板
+    pub template: Option<TemplateSaveReqDto>,
+
+    /// 订单金额
+    /// 示例: 128.5
+    pub order_amount: Decimal,
+
+    /// 订单中的商品
+    #[serde(default)]
+    pub goods: Vec<PreviewGoodsDto>,
+}
+
+/// 优惠试算的订单商品
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewGoodsDto {
+    /// 商品编码
+    pub goods_number: String,
+
+    /// 该商品在订单中的金额
+    pub amount: Decimal,
+}
+
+impl Validate for TemplatePreviewReqDto {
+    fn rules(&self, v: &mut Validator) {
+        v.check(
+            "couponTemplateId",
+            self.coupon_template_id.is_some() != self.template.is_some(),
+            "模板ID和模板内容必须且只能填写一个",
+        )
+        .amount("orderAmount", self.order_amount);
+
+        let mut goods_amount = Some(Decimal::ZERO);
+        for (index, goods) in self.goods.iter().enumerate() {
+            v.not_blank(
+                &format!("goods[{}].goodsNumber", index),
+                &goods.goods_number,
+            )
+            .amount(&format!("goods[{}].amount", index), goods.amount);
+            goods_amount = goods_amount.and_then(|sum| sum.checked_add(goods.amount));
+        }
+        v.check(
+            "goods",
+            goods_amount.is_some_and(|amount| amount <= self.order_amount),
+            "商品金额合计不能超过订单金额",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn valid_dto() -> TemplateSaveReqDto {
+        TemplateSaveReqDto {
+            name: "用户下单满10减3特大优惠".to_string(),
+            shop_number: None,
+            source: CouponSource::Shop,
+            target: CouponTarget::StoreWide,
+            goods: String::new(),
+            r#type: CouponType::FullReduction,
+            valid_start_time: Some(Utc::now()),
+            valid_end_time: Some(Utc::now() + Duration::days(7)),
+            stock: 200,
+            receive_rule: r#"{"limitPerPerson":1,"usageInstructions":"3"}"#.to_string(),
+            consume_rule: r#"{"termsOfUse":10,"maximumDiscountAmount":3}"#.to_string(),
+        }
+    }
+
+    #[test]
+    fn valid_template_passes() {
+        assert!(valid_dto().validate().is_ok());
+    }
+
+    #[test]
+    fn collects_every_invalid_field() {
+        let dto = TemplateSaveReqDto {
+            name: " ".to_string(),
+            target: CouponTarget::SpecificGoods,
+            stock: -1,
+            valid_end_time: Some(Utc::now() - Duration::days(1)),
+            consume_rule: r#"{"maximumDiscountAmount":3}"#.to_string(),
+            ..valid_dto()
+        };
+
+        let Err(AppError::Validation { errors, .. }) = dto.validate() else {
+            panic!("expected validation errors");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "name",
+                "stock",
+                "goods",
+                "validEndTime",
+                "validEndTime",
+                "consumeRule.termsOfUse"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_preview_amounts_that_could_overflow() {
+        let goods = PreviewGoodsDto {
+            goods_number: "G001".to_string(),
+            amount: Decimal::MAX,
+        };
+        let dto = TemplatePreviewReqDto {
+            coupon_template_id: Some(1),
+            template: None,
+            order_amount: Decimal::MAX,
+            goods: vec![goods.clone(), goods],
+        };
+
+        let Err(AppError::Validation { errors, .. }) = dto.validate() else {
+            panic!("expected validation errors");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["orderAmount", "goods[0].amount", "goods[1].amount", "goods"]
+        );
+    }
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// 领取优惠券的请求 DTO
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CouponClaimReqDto {
+    /// 优惠券模板ID
+    pub coupon_template_id: i64,
+}
@@ -0,0 +1,64 @@
+use crate::coupon_task_fail_file::TaskFile;
+use crate::dto::file_req::FileDownloadReqDto;
+use crate::template_export::ExportFormat;
+use crate::template_import::is_xlsx;
+use crate::AppState;
+use actix_web::web::Data;
+use common::app_error::AppError;
+use common::storage::local;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+
+#[async_trait]
+pub trait FileService: Send + Sync {
+    async fn download_signed(
+        &self,
+        req: FileDownloadReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<TaskFile, AppError>;
+}
+
+pub struct FileServiceImpl;
+
+#[async_trait]
+impl FileService for FileServiceImpl {
+    /// 通过本地存储生成的临时下载链接下载文件
+    ///
+    /// 对象存储生成的链接直接指向对象存储，不经过这里
+    ///
+    /// # 参数
+    /// * `req` - 临时下载链接中的文件地址、过期时间和签名
+    /// * `app_state` - 应用程序状态，包含配置和文件存储
+    ///
+    /// # 返回
+    /// * `Result<TaskFile, AppError>` - 成功时返回文件内容，签名无效或链接已过期时返回错误
+    async fn download_signed(
+        &self,
+        req: FileDownloadReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<TaskFile, AppError> {
+        let secret = &app_state.config.storage.local.secret;
+        if !local::verify(secret, &req.key, req.expires, &req.signature) {
+            return Err(AppError::forbidden("下载链接无效或已过期"));
+        }
+
+        let content = app_state.storage.download(&req.key).await?;
+        let format = if is_xlsx(Some(&req.key), &content) {
+            ExportFormat::Xlsx
+        } else {
+            ExportFormat::Csv
+        };
+        let file_name = req.key.rsplit('/').next().unwrap_or(&req.key).to_string();
+        Ok(TaskFile {
+            file_name,
+            content_type: format.content_type(),
+            content,
+        })
+    }
+}
+
+static FILE_SERVICE: Lazy<FileServiceImpl> = Lazy::new(|| FileServiceImpl);
+
+pub fn file_service() -> &'static dyn FileService {
+    &*FILE_SERVICE
+}
@@ -1,12 +1,35 @@
-use std::sync::Arc;
+use common::config::AppConfig;
+use common::storage::Storage;
 use sea_orm::DatabaseConnection;
+use std::sync::Arc;
 
-pub mod template;
-pub mod dto;
 pub mod auth;
+pub mod cache;
+pub mod coupon_task;
+pub mod coupon_task_executor;
+pub mod coupon_task_fail_file;
+pub mod coupon_task_progress;
+pub mod dto;
+pub mod file;
+pub mod notify;
+pub mod template;
+pub mod template_audit;
+pub mod template_export;
+pub mod template_import;
+pub mod template_log;
+pub mod template_preview;
+pub mod template_statistics;
+pub mod user_coupon;
+pub mod user_popup;
+pub mod validation;
+
+#[cfg(test)]
+mod test_support;
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub database: Arc<DatabaseConnection>,
+    pub config: Arc<AppConfig>,
+    /// 分发任务用户文件和失败文件的存储
+    pub storage: Arc<dyn Storage>,
 }
-
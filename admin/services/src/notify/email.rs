@@ -0,0 +1,186 @@
+use crate::notify::{Notification, Notifier, NotifyError};
+use crate::AppState;
+use actix_web::web::Data;
+use common::app_error::AppError;
+use common::config::{EmailConfig, NotifyConfig};
+use data::dao::user_contact::user_contact_dao;
+use data::enums::NotifyType;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use once_cell::sync::{Lazy, OnceCell};
+use sea_orm::prelude::async_trait::async_trait;
+
+/// 邮件通知，通过 SMTP 发送到用户联系方式中的邮箱
+pub struct EmailNotifier {
+    /// 首次发送时根据配置创建
+    transport: OnceCell<AsyncSmtpTransport<Tokio1Executor>>,
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn notify_type(&self) -> NotifyType {
+        NotifyType::Email
+    }
+
+    fn is_configured(&self, config: &NotifyConfig) -> bool {
+        config.email.host.is_some()
+    }
+
+    async fn send(
+        &self,
+        notification: &Notification,
+        app_state: &Data<AppState>,
+    ) -> Result<(), NotifyError> {
+        let config = &app_state.config.notify.email;
+        let transport = self
+            .transport
+            .get_or_try_init(|| build_transport(config))
+            .map_err(NotifyError::Permanent)?;
+        let email = user_contact_dao()
+            .find_by_user_id(&app_state.database, notification.user_id)
+            .await?
+            .and_then(|contact| contact.email)
+            .filter(|email| !email.trim().is_empty())
+            .ok_or_else(|| NotifyError::permanent("用户没有邮箱"))?;
+
+        deliver(transport, &config.from, &email, notification).await
+    }
+}
+
+/// 根据配置创建 SMTP 连接，未开启 STARTTLS 时使用明文连接；配置错误重试也不会成功
+fn build_transport(config: &EmailConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>, AppError> {
+    let host = config
+        .host
+        .as_deref()
+        .ok_or_else(|| AppError::internal_error("未配置邮件服务"))?;
+    let mut builder = if config.starttls {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|err| AppError::internal_error(format!("邮件服务配置错误: {}", err)))?
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+    }
+    .port(config.port);
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    Ok(builder.build())
+}
+
+/// 发送邮件；地址错误和 SMTP 服务的永久性拒绝 (5xx) 不再重试，连接失败等其他错误可以重试
+async fn deliver(
+    transport: &AsyncSmtpTransport<Tokio1Executor>,
+    from: &str,
+    to: &str,
+    notification: &Notification,
+) -> Result<(), NotifyError> {
+    let message = Message::builder()
+        .from(
+            from.parse()
+                .map_err(|err| NotifyError::permanent(format!("发件人地址错误: {}", err)))?,
+        )
+        .to(to
+            .parse()
+            .map_err(|err| NotifyError::permanent(format!("用户邮箱错误: {}", err)))?)
+        .subject(notification.title.as_str())
+        .header(ContentType::TEXT_PLAIN)
+        .body(notification.content.clone())
+        .map_err(|err| NotifyError::permanent(format!("生成邮件失败: {}", err)))?;
+
+    transport.send(message).await.map_err(|err| {
+        let msg = format!("发送邮件失败: {}", err);
+        if err.is_permanent() {
+            NotifyError::permanent(msg)
+        } else {
+            NotifyError::transient(msg)
+        }
+    })?;
+    Ok(())
+}
+
+static EMAIL_NOTIFIER: Lazy<EmailNotifier> = Lazy::new(|| EmailNotifier {
+    transport: OnceCell::new(),
+});
+
+pub fn email_notifier() -> &'static dyn Notifier {
+    &*EMAIL_NOTIFIER
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    /// 只接收一封邮件的本地 SMTP 服务，返回端口和收到的全部命令及邮件内容
+    fn smtp_sink() -> (u16, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut received = Vec::new();
+            let mut in_data = false;
+            writer.write_all(b"220 localhost ESMTP\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = if in_data {
+                    if line == "." {
+                        in_data = false;
+                        b"250 OK\r\n"
+                    } else {
+                        b""
+                    }
+                } else if line.starts_with("EHLO") {
+                    b"250 localhost\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 End data with <CR><LF>.<CR><LF>\r\n"
+                } else if line == "QUIT" {
+                    writer.write_all(b"221 Bye\r\n").unwrap();
+                    received.push(line);
+                    break;
+                } else {
+                    b"250 OK\r\n"
+                };
+                writer.write_all(reply).unwrap();
+                received.push(line);
+            }
+            received
+        });
+        (port, handle)
+    }
+
+    #[actix_web::test]
+    async fn sends_email_through_smtp() {
+        let (port, sink) = smtp_sink();
+        let config = EmailConfig {
+            host: Some("127.0.0.1".to_string()),
+            port,
+            ..EmailConfig::default()
+        };
+        let notification = Notification {
+            batch_id: 1,
+            user_id: 1001,
+            title: "优惠券到账提醒".to_string(),
+            content: "您获得了一张优惠券".to_string(),
+        };
+
+        let transport = build_transport(&config).unwrap();
+        deliver(&transport, &config.from, "user@example.com", &notification)
+            .await
+            .unwrap();
+        drop(transport);
+
+        let received = sink.join().unwrap();
+        assert!(received.contains(&"MAIL FROM:<coupon@localhost>".to_string()));
+        assert!(received.contains(&"RCPT TO:<user@example.com>".to_string()));
+        assert!(received.iter().any(|line| line.starts_with("Subject:")));
+    }
+}
@@ -0,0 +1,48 @@
+use crate::notify::{Notification, Notifier, NotifyError};
+use crate::AppState;
+use actix_web::web::Data;
+use chrono::Utc;
+use data::dao::user_message::user_message_dao;
+use data::entity::user_message;
+use data::enums::NotifyType;
+use data::soft_delete::NOT_DELETED;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::TransactionTrait;
+
+/// 站内信通知，写入用户站内信表
+pub struct InAppNotifier;
+
+#[async_trait]
+impl Notifier for InAppNotifier {
+    fn notify_type(&self) -> NotifyType {
+        NotifyType::InApp
+    }
+
+    async fn send(
+        &self,
+        notification: &Notification,
+        app_state: &Data<AppState>,
+    ) -> Result<(), NotifyError> {
+        let message = user_message::Model {
+            id: 0,
+            user_id: notification.user_id,
+            title: notification.title.clone(),
+            content: notification.content.clone(),
+            read_flag: 0,
+            read_time: None,
+            create_time: Some(Utc::now()),
+            del_flag: NOT_DELETED,
+        };
+        let txn = app_state.database.begin().await?;
+        user_message_dao().create(&txn, &message).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+static IN_APP_NOTIFIER: Lazy<InAppNotifier> = Lazy::new(|| InAppNotifier);
+
+pub fn in_app_notifier() -> &'static dyn Notifier {
+    &*IN_APP_NOTIFIER
+}
@@ -0,0 +1,427 @@
+pub mod email;
+pub mod in_app;
+pub mod popup;
+pub mod sms;
+
+use crate::AppState;
+use actix_web::web::Data;
+use chrono::{DateTime, Utc};
+use common::app_error::AppError;
+use common::config::NotifyConfig;
+use common::datetime::format_gmt8;
+use data::dao::coupon_notify_fail::coupon_notify_fail_dao;
+use data::dao::template::template_dao;
+use data::entity::{coupon_notify_fail, coupon_task, template};
+use data::enums::{NotifyFailStatus, NotifyType};
+use futures_util::stream::{self, StreamExt};
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::TransactionTrait;
+use std::fmt;
+
+/// 优惠券到账通知的标题
+const TITLE: &str = "优惠券到账提醒";
+/// 同一种通知方式同时发送的通知数量
+const SEND_CONCURRENCY: usize = 16;
+/// 定时任务取出待重试记录后，在这段时间内其他实例不会再取出它们；进程在重试过程中退出时，
+/// 未处理完的记录在这段时间后重新被重试
+const RETRY_LEASE_SECONDS: i64 = 300;
+
+/// 发送给单个用户的通知
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// 分发任务的批次ID，用于记录通知失败
+    pub batch_id: i64,
+    /// 接收通知的用户ID
+    pub user_id: i64,
+    /// 标题
+    pub title: String,
+    /// 内容
+    pub content: String,
+}
+
+/// 通知发送失败
+#[derive(Debug)]
+pub enum NotifyError {
+    /// 重试也不会成功的失败，例如用户没有邮箱或手机号，只记录一次不再重试
+    Permanent(AppError),
+    /// 暂时性的失败，例如服务不可用或超时，由定时任务按间隔重试
+    Transient(AppError),
+}
+
+impl NotifyError {
+    pub fn permanent(msg: impl ToString) -> Self {
+        NotifyError::Permanent(AppError::validation_error(msg))
+    }
+
+    pub fn transient(msg: impl ToString) -> Self {
+        NotifyError::Transient(AppError::internal_error(msg))
+    }
+
+    pub fn is_permanent(&self) -> bool {
+        matches!(self, NotifyError::Permanent(_))
+    }
+}
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyError::Permanent(err) | NotifyError::Transient(err) => err.fmt(f),
+        }
+    }
+}
+
+/// 数据库等内部错误视为暂时性失败
+impl From<AppError> for NotifyError {
+    fn from(err: AppError) -> Self {
+        NotifyError::Transient(err)
+    }
+}
+
+impl From<sea_orm::DbErr> for NotifyError {
+    fn from(err: sea_orm::DbErr) -> Self {
+        NotifyError::Transient(AppError::from(err))
+    }
+}
+
+/// 通知发送方式
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// 对应的通知方式
+    fn notify_type(&self) -> NotifyType;
+
+    /// 是否已配置发送所需的服务，未配置的通知方式直接跳过
+    fn is_configured(&self, _config: &NotifyConfig) -> bool {
+        true
+    }
+
+    /// 向用户发送一条通知，只发送一次，失败时返回错误
+    async fn send(
+        &self,
+        notification: &Notification,
+        app_state: &Data<AppState>,
+    ) -> Result<(), NotifyError>;
+}
+
+/// 根据通知方式取得对应的通知实现
+pub fn notifier(notify_type: &NotifyType) -> &'static dyn Notifier {
+    match notify_type {
+        NotifyType::InApp => in_app::in_app_notifier(),
+        NotifyType::Popup => popup::popup_notifier(),
+        NotifyType::Email => email::email_notifier(),
+        NotifyType::Sms => sms::sms_notifier(),
+    }
+}
+
+/// 按分发任务的通知方式，在后台通知发放成功的用户
+///
+/// 每条通知只立即发送一次，暂时性失败写入通知失败记录，由定时任务按间隔重试；用户没有联系方式等
+/// 永久性失败只记录不重试。未配置服务的通知方式跳过。通知结果不影响优惠券发放
+pub(crate) fn dispatch(task: &coupon_task::Model, user_ids: Vec<i64>, app_state: Data<AppState>) {
+    let notify_types: Vec<NotifyType> = task
+        .notify_type
+        .as_deref()
+        .map(NotifyType::parse_list)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|notify_type| {
+            let configured = notifier(notify_type).is_configured(&app_state.config.notify);
+            if !configured {
+                info!(
+                    "未配置{}服务，跳过通知, 批次ID: {}",
+                    notify_type.label(),
+                    task.batch_id
+                );
+            }
+            configured
+        })
+        .collect();
+    if notify_types.is_empty() || user_ids.is_empty() {
+        return;
+    }
+    let batch_id = task.batch_id;
+    let coupon_template_id = task.coupon_template_id;
+
+    tokio::spawn(async move {
+        let template = match template_dao()
+            .find_by_id(&app_state.database, coupon_template_id)
+            .await
+        {
+            Ok(Some(template)) => template,
+            Ok(None) => return,
+            Err(err) => {
+                error!(
+                    "查询通知的优惠券模板失败, 批次ID: {}, 错误: {}",
+                    batch_id, err
+                );
+                return;
+            }
+        };
+        let notifications: Vec<Notification> = user_ids
+            .iter()
+            .map(|user_id| new_notification(batch_id, *user_id, &template))
+            .collect();
+
+        for notify_type in &notify_types {
+            let notifier = notifier(notify_type);
+            stream::iter(&notifications)
+                .for_each_concurrent(SEND_CONCURRENCY, |notification| {
+                    send_once(notifier, notification, &app_state)
+                })
+                .await;
+        }
+        info!(
+            "优惠券到账通知发送完成, 批次ID: {}, 用户数: {}, 通知方式: {}",
+            batch_id,
+            notifications.len(),
+            notify_types
+                .iter()
+                .map(NotifyType::label)
+                .collect::<Vec<_>>()
+                .join("、")
+        );
+    });
+}
+
+fn new_notification(batch_id: i64, user_id: i64, template: &template::Model) -> Notification {
+    let content = match &template.valid_end_time {
+        Some(end) => format!(
+            "您获得了一张「{}」优惠券，有效期至 {}，请及时使用",
+            template.name,
+            format_gmt8(end)
+        ),
+        None => format!("您获得了一张「{}」优惠券，请及时使用", template.name),
+    };
+    Notification {
+        batch_id,
+        user_id,
+        title: TITLE.to_string(),
+        content,
+    }
+}
+
+/// 首次发送通知，失败时写入通知失败记录
+async fn send_once(
+    notifier: &dyn Notifier,
+    notification: &Notification,
+    app_state: &Data<AppState>,
+) {
+    let Err(err) = notifier.send(notification, app_state).await else {
+        return;
+    };
+    let next_retry_time = next_retry_time(&err, 1, &app_state.config.notify, Utc::now());
+    warn!(
+        "{}通知发送失败, 用户ID: {}, {}, 错误: {}",
+        notifier.notify_type().label(),
+        notification.user_id,
+        if next_retry_time.is_some() {
+            "稍后重试"
+        } else {
+            "不再重试"
+        },
+        err
+    );
+
+    let now = Utc::now();
+    let record = coupon_notify_fail::Model {
+        id: 0,
+        batch_id: notification.batch_id,
+        user_id: notification.user_id,
+        notify_type: notifier.notify_type(),
+        title: notification.title.clone(),
+        content: notification.content.clone(),
+        attempts: 1,
+        reason: reason(&err),
+        status: match next_retry_time {
+            Some(_) => NotifyFailStatus::Retrying,
+            None => NotifyFailStatus::Abandoned,
+        },
+        next_retry_time,
+        create_time: Some(now),
+        update_time: Some(now),
+    };
+    let result = async {
+        let txn = app_state.database.begin().await?;
+        coupon_notify_fail_dao().create(&txn, &record).await?;
+        txn.commit().await
+    }
+    .await;
+    if let Err(err) = result {
+        error!(
+            "写入通知失败记录失败, 批次ID: {}, 用户ID: {}, 错误: {}",
+            notification.batch_id, notification.user_id, err
+        );
+    }
+}
+
+/// 计算下次重试时间：永久性失败或已达到最大发送次数时返回 `None`，否则间隔随已发送次数翻倍
+fn next_retry_time(
+    err: &NotifyError,
+    attempts: u32,
+    config: &NotifyConfig,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if err.is_permanent() || attempts >= config.max_attempts {
+        return None;
+    }
+    let seconds = config
+        .retry_interval_seconds
+        .saturating_mul(1 << (attempts - 1).min(16));
+    Some(now + chrono::Duration::seconds(i64::try_from(seconds).unwrap_or(i64::MAX / 1000)))
+}
+
+fn reason(err: &NotifyError) -> String {
+    err.to_string().chars().take(512).collect()
+}
+
+#[async_trait]
+pub trait NotifyService: Send + Sync {
+    async fn retry_failed(
+        &self,
+        batch_size: u64,
+        app_state: Data<AppState>,
+    ) -> Result<usize, AppError>;
+}
+
+pub struct NotifyServiceImpl;
+
+#[async_trait]
+impl NotifyService for NotifyServiceImpl {
+    /// 重试到达重试时间的失败通知
+    ///
+    /// 由定时任务周期调用。待重试记录在一个事务中加锁并推迟下次重试时间后提交，已被其他实例锁定的记录
+    /// 会被跳过；之后逐条重新发送，成功的记录删除，失败的记录累加发送次数并按间隔安排下次重试，
+    /// 达到最大次数、永久性失败或通知方式已不再配置时放弃
+    ///
+    /// # 参数
+    /// * `batch_size` - 每次最多重试的通知数量
+    /// * `app_state` - 应用程序状态，包含数据库连接和配置
+    ///
+    /// # 返回
+    /// * `Result<usize, AppError>` - 成功时返回重试成功的通知数量，失败时返回错误
+    async fn retry_failed(
+        &self,
+        batch_size: u64,
+        app_state: Data<AppState>,
+    ) -> Result<usize, AppError> {
+        let now = Utc::now();
+        let txn = app_state.database.begin().await?;
+        let records = coupon_notify_fail_dao()
+            .lock_due(&txn, now, batch_size)
+            .await?;
+        let ids: Vec<i64> = records.iter().map(|record| record.id).collect();
+        coupon_notify_fail_dao()
+            .postpone(
+                &txn,
+                &ids,
+                now + chrono::Duration::seconds(RETRY_LEASE_SECONDS),
+            )
+            .await?;
+        txn.commit().await?;
+
+        let results: Vec<bool> = stream::iter(records)
+            .map(|record| {
+                let app_state = app_state.clone();
+                async move { retry_one(&record, &app_state).await }
+            })
+            .buffer_unordered(SEND_CONCURRENCY)
+            .collect()
+            .await;
+        Ok(results.into_iter().filter(|succeeded| *succeeded).count())
+    }
+}
+
+/// 重新发送一条失败通知并更新记录，发送成功时返回 `true`
+async fn retry_one(record: &coupon_notify_fail::Model, app_state: &Data<AppState>) -> bool {
+    let notifier = notifier(&record.notify_type);
+    let notification = Notification {
+        batch_id: record.batch_id,
+        user_id: record.user_id,
+        title: record.title.clone(),
+        content: record.content.clone(),
+    };
+    let attempts = u32::try_from(record.attempts).unwrap_or(0) + 1;
+    let result = if notifier.is_configured(&app_state.config.notify) {
+        notifier.send(&notification, app_state).await
+    } else {
+        Err(NotifyError::permanent(format!(
+            "未配置{}服务",
+            record.notify_type.label()
+        )))
+    };
+
+    let updated = async {
+        let txn = app_state.database.begin().await?;
+        match &result {
+            Ok(()) => coupon_notify_fail_dao().delete(&txn, record.id).await?,
+            Err(err) => {
+                let next_retry_time =
+                    next_retry_time(err, attempts, &app_state.config.notify, Utc::now());
+                coupon_notify_fail_dao()
+                    .record_attempt(
+                        &txn,
+                        record.id,
+                        attempts as i32,
+                        reason(err),
+                        next_retry_time,
+                    )
+                    .await?
+            }
+        };
+        txn.commit().await
+    }
+    .await;
+    if let Err(err) = updated {
+        error!("更新通知失败记录失败, 记录ID: {}, 错误: {}", record.id, err);
+    }
+
+    match result {
+        Ok(()) => true,
+        Err(err) => {
+            warn!(
+                "{}通知重试失败, 用户ID: {}, 第 {} 次, 错误: {}",
+                notifier.notify_type().label(),
+                record.user_id,
+                attempts,
+                err
+            );
+            false
+        }
+    }
+}
+
+static NOTIFY_SERVICE: Lazy<NotifyServiceImpl> = Lazy::new(|| NotifyServiceImpl);
+
+pub fn notify_service() -> &'static dyn NotifyService {
+    &*NOTIFY_SERVICE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_transient_failures_with_doubling_interval() {
+        let config = NotifyConfig {
+            max_attempts: 3,
+            retry_interval_seconds: 60,
+            ..NotifyConfig::default()
+        };
+        let now = Utc::now();
+        let transient = NotifyError::transient("连接超时");
+
+        assert_eq!(
+            next_retry_time(&transient, 1, &config, now),
+            Some(now + chrono::Duration::seconds(60))
+        );
+        assert_eq!(
+            next_retry_time(&transient, 2, &config, now),
+            Some(now + chrono::Duration::seconds(120))
+        );
+        assert_eq!(next_retry_time(&transient, 3, &config, now), None);
+
+        let permanent = NotifyError::permanent("用户没有邮箱");
+        assert_eq!(next_retry_time(&permanent, 1, &config, now), None);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::notify::{Notification, Notifier, NotifyError};
+use crate::AppState;
+use actix_web::web::Data;
+use chrono::Utc;
+use data::dao::user_popup::user_popup_dao;
+use data::entity::user_popup;
+use data::enums::NotifyType;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::TransactionTrait;
+
+/// 弹框推送通知，写入用户弹框消息表，用户端连接时取走
+pub struct PopupNotifier;
+
+#[async_trait]
+impl Notifier for PopupNotifier {
+    fn notify_type(&self) -> NotifyType {
+        NotifyType::Popup
+    }
+
+    async fn send(
+        &self,
+        notification: &Notification,
+        app_state: &Data<AppState>,
+    ) -> Result<(), NotifyError> {
+        let popup = user_popup::Model {
+            id: 0,
+            user_id: notification.user_id,
+            title: notification.title.clone(),
+            content: notification.content.clone(),
+            popped_flag: 0,
+            create_time: Some(Utc::now()),
+        };
+        let txn = app_state.database.begin().await?;
+        user_popup_dao().create(&txn, &popup).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+static POPUP_NOTIFIER: Lazy<PopupNotifier> = Lazy::new(|| PopupNotifier);
+
+pub fn popup_notifier() -> &'static dyn Notifier {
+    &*POPUP_NOTIFIER
+}
@@ -0,0 +1,216 @@
+use crate::notify::{Notification, Notifier, NotifyError};
+use crate::AppState;
+use actix_web::web::Data;
+use common::app_error::AppError;
+use common::config::{NotifyConfig, SmsConfig};
+use data::dao::user_contact::user_contact_dao;
+use data::enums::NotifyType;
+use once_cell::sync::{Lazy, OnceCell};
+use sea_orm::prelude::async_trait::async_trait;
+use serde::Serialize;
+use std::time::Duration;
+
+/// 发送给短信服务商的请求内容
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SmsRequest<'a> {
+    /// 手机号
+    phone: &'a str,
+    /// 短信内容
+    content: &'a str,
+}
+
+/// 短信通知，通过短信服务商的 HTTP 接口发送到用户联系方式中的手机号
+///
+/// 以 JSON 请求体 `{"phone": "...", "content": "..."}` 调用配置的发送接口，返回非 2xx 状态码时视为失败；
+/// 4xx 表示请求本身有误，不再重试，5xx 和超时等错误可以重试
+pub struct SmsNotifier {
+    /// 首次发送时根据配置创建
+    client: OnceCell<reqwest::Client>,
+}
+
+#[async_trait]
+impl Notifier for SmsNotifier {
+    fn notify_type(&self) -> NotifyType {
+        NotifyType::Sms
+    }
+
+    fn is_configured(&self, config: &NotifyConfig) -> bool {
+        config.sms.url.is_some()
+    }
+
+    async fn send(
+        &self,
+        notification: &Notification,
+        app_state: &Data<AppState>,
+    ) -> Result<(), NotifyError> {
+        let config = &app_state.config.notify.sms;
+        let client = self
+            .client
+            .get_or_try_init(|| build_client(config))
+            .map_err(NotifyError::Permanent)?;
+        let phone = user_contact_dao()
+            .find_by_user_id(&app_state.database, notification.user_id)
+            .await?
+            .and_then(|contact| contact.phone)
+            .filter(|phone| !phone.trim().is_empty())
+            .ok_or_else(|| NotifyError::permanent("用户没有手机号"))?;
+
+        deliver(client, config, &phone, notification).await
+    }
+}
+
+fn build_client(config: &SmsConfig) -> Result<reqwest::Client, AppError> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_millis(config.timeout_ms))
+        .build()
+        .map_err(|err| AppError::internal_error(format!("创建短信服务客户端失败: {}", err)))
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    config: &SmsConfig,
+    phone: &str,
+    notification: &Notification,
+) -> Result<(), NotifyError> {
+    let url = config
+        .url
+        .as_deref()
+        .ok_or_else(|| NotifyError::permanent("未配置短信服务"))?;
+    let mut request = client.post(url).json(&SmsRequest {
+        phone,
+        content: &notification.content,
+    });
+    if let Some(api_key) = &config.api_key {
+        request = request.bearer_auth(api_key);
+    }
+
+    request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| {
+            let msg = format!("发送短信失败: {}", err);
+            if err.status().is_some_and(|status| status.is_client_error()) {
+                NotifyError::permanent(msg)
+            } else {
+                NotifyError::transient(msg)
+            }
+        })?;
+    Ok(())
+}
+
+static SMS_NOTIFIER: Lazy<SmsNotifier> = Lazy::new(|| SmsNotifier {
+    client: OnceCell::new(),
+});
+
+pub fn sms_notifier() -> &'static dyn Notifier {
+    &*SMS_NOTIFIER
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    /// 只处理一个请求的本地短信服务商，以指定状态行响应，返回接口地址和收到的请求
+    fn fake_provider(status_line: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sms/send", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            request.push_str(&String::from_utf8(body).unwrap());
+            writer
+                .write_all(
+                    format!(
+                        "{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        status_line
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+            request
+        });
+        (url, handle)
+    }
+
+    fn notification() -> Notification {
+        Notification {
+            batch_id: 1,
+            user_id: 1001,
+            title: "优惠券到账提醒".to_string(),
+            content: "您获得了一张优惠券".to_string(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn posts_sms_to_provider() {
+        let (url, provider) = fake_provider("HTTP/1.1 200 OK");
+        let config = SmsConfig {
+            url: Some(url),
+            api_key: Some("secret".to_string()),
+            ..SmsConfig::default()
+        };
+
+        let client = build_client(&config).unwrap();
+        deliver(&client, &config, "13800000000", &notification())
+            .await
+            .unwrap();
+
+        let request = provider.join().unwrap();
+        assert!(request.starts_with("POST /sms/send "));
+        assert!(request
+            .to_ascii_lowercase()
+            .contains("authorization: bearer secret"));
+        assert!(request.ends_with(r#"{"phone":"13800000000","content":"您获得了一张优惠券"}"#));
+    }
+
+    #[actix_web::test]
+    async fn fails_when_provider_rejects() {
+        let (url, provider) = fake_provider("HTTP/1.1 500 Internal Server Error");
+        let config = SmsConfig {
+            url: Some(url),
+            ..SmsConfig::default()
+        };
+
+        let client = build_client(&config).unwrap();
+        let result = deliver(&client, &config, "13800000000", &notification()).await;
+        provider.join().unwrap();
+        assert!(matches!(result, Err(NotifyError::Transient(_))));
+    }
+
+    #[actix_web::test]
+    async fn does_not_retry_rejected_request() {
+        let (url, provider) = fake_provider("HTTP/1.1 400 Bad Request");
+        let config = SmsConfig {
+            url: Some(url),
+            ..SmsConfig::default()
+        };
+
+        let client = build_client(&config).unwrap();
+        let result = deliver(&client, &config, "13800000000", &notification()).await;
+        provider.join().unwrap();
+        assert!(matches!(result, Err(NotifyError::Permanent(_))));
+    }
+}
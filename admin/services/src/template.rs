@@ -1,11 +1,25 @@
-use crate::dto::template_req::TemplateSaveReqDto;
+use crate::auth::SHOP_NUMBER;
+use crate::dto::page_req::check_page;
+use crate::dto::template_req::{
+    TemplateCopyReqDto, TemplateIdReqDto, TemplateNumberReqDto, TemplatePageQueryReqDto,
+    TemplateSaveReqDto, TemplateUpdateReqDto,
+};
+use crate::template_log;
+use crate::validation::{Validate, Validator};
 use crate::AppState;
 use actix_web::web::Data;
+use chrono::{Duration, Utc};
 use common::app_error::AppError;
+use common::transfer::PageResult;
+use data::dao::user_coupon::user_coupon_dao;
+use data::enums::{AuditStatus, CouponStatus};
+use data::rule::{ConsumeRule, ReceiveRule};
+use data::soft_delete::{DELETED, NOT_DELETED};
 use data::{dao::template::template_dao, entity::template};
 use log::{error, info};
 use once_cell::sync::Lazy;
 use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::TransactionTrait;
 
 #[async_trait]
 pub trait TemplateService: Send + Sync {
@@ -14,8 +28,66 @@ pub trait TemplateService: Send + Sync {
         req: TemplateSaveReqDto,
         app_state: Data<AppState>,
     ) -> Result<i64, AppError>;
+
+    async fn page_template(
+        &self,
+        req: TemplatePageQueryReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<PageResult<template::Model>, AppError>;
+
+    async fn find_template(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError>;
+
+    async fn increase_number(
+        &self,
+        req: TemplateNumberReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<i32, AppError>;
+
+    async fn terminate_template(
+        &self,
+        req: TemplateIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError>;
+
+    async fn delete_template(
+        &self,
+        req: TemplateIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError>;
+
+    async fn restore_template(
+        &self,
+        req: TemplateIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError>;
+
+    async fn update_template(
+        &self,
+        req: TemplateUpdateReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError>;
+
+    async fn copy_template(
+        &self,
+        id: i64,
+        req: TemplateCopyReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<i64, AppError>;
+
+    async fn end_expired_templates(
+        &self,
+        batch_size: u64,
+        app_state: Data<AppState>,
+    ) -> Result<u64, AppError>;
 }
 
+/// 逻辑删除后允许恢复的天数
+pub const RESTORE_RETENTION_DAYS: i64 = 30;
+
 pub struct TemplateServiceImpl;
 
 #[async_trait]
@@ -35,29 +107,523 @@ impl TemplateService for TemplateServiceImpl {
         req: TemplateSaveReqDto,
         app_state: Data<AppState>,
     ) -> Result<i64, AppError> {
+        // 一次性校验全部字段，返回所有不通过的字段
+        req.validate()?;
+
         // 使用 From trait 转换请求DTO为数据库模型
         let template_model = template::Model::try_from(req)?;
 
-        // 获取数据库连接
-        let db = &app_state.database;
+        // 开启事务，模板与操作日志一起提交
+        let txn = app_state.database.begin().await?;
 
         // 获取 DAO 实例并调用方法
         let dao = template_dao();
 
         // 使用DAO保存模板
-        match dao.create(db, &template_model).await {
-            Ok(created_model) => {
-                info!(
-                    "创建优惠券模板成功, 模板ID: {}, 名称: {}",
-                    created_model.id, created_model.name
-                );
-                Ok(created_model.id)
-            }
+        let created_model = match dao.create(&txn, &template_model).await {
+            Ok(created_model) => created_model,
             Err(err) => {
                 error!("创建优惠券模板失败: {}", err);
-                Err(AppError::from(err))
+                return Err(AppError::from(err));
+            }
+        };
+        template_log::record(
+            &txn,
+            format!("创建优惠券模板：{}", created_model.name),
+            None,
+            &created_model,
+        )
+        .await?;
+        txn.commit().await?;
+
+        info!(
+            "创建优惠券模板成功, 模板ID: {}, 名称: {}",
+            created_model.id, created_model.name
+        );
+        Ok(created_model.id)
+    }
+
+    /// 分页查询当前店铺的优惠券模板
+    ///
+    /// 支持按名称模糊查询，以及按类型、对象、状态、商品编码和有效期过滤，已删除的模板不会返回
+    ///
+    /// # 参数
+    /// * `req` - 分页查询请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<PageResult<template::Model>, AppError>` - 成功时返回分页结果，单页数量超过上限时返回错误
+    async fn page_template(
+        &self,
+        req: TemplatePageQueryReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<PageResult<template::Model>, AppError> {
+        check_page(req.current, req.size)?;
+
+        let filter = req.to_filter(SHOP_NUMBER); //TODO: 需要实现用户登录模块
+        let (records, total) = template_dao()
+            .page(&app_state.database, &filter, req.current, req.size)
+            .await
+            .map_err(|err| {
+                error!("分页查询优惠券模板失败: {}", err);
+                AppError::from(err)
+            })?;
+
+        Ok(PageResult::new(req.current, req.size, total, records))
+    }
+
+    /// 查询当前店铺的优惠券模板详情
+    ///
+    /// 模板不存在、已删除或不属于当前店铺时，统一按未找到处理
+    ///
+    /// # 参数
+    /// * `id` - 优惠券模板ID
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<template::Model, AppError>` - 成功时返回模板详情，失败时返回错误
+    async fn find_template(
+        &self,
+        id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError> {
+        let template = template_dao()
+            .find_by_id(&app_state.database, id)
+            .await
+            .map_err(|err| {
+                error!("查询优惠券模板失败, 模板ID: {}, 错误: {}", id, err);
+                AppError::from(err)
+            })?;
+
+        match template {
+            Some(model) if model.shop_number == SHOP_NUMBER => Ok(model), //TODO: 需要实现用户登录模块
+            _ => Err(AppError::not_found("优惠券模板", id)),
+        }
+    }
+
+    /// 增加优惠券模板库存
+    ///
+    /// 在事务中锁定模板行后执行带条件的原子更新，多个后台页面同时追加库存时不会互相覆盖
+    ///
+    /// # 参数
+    /// * `req` - 增加库存请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<i32, AppError>` - 成功时返回增加后的库存，失败时返回错误
+    async fn increase_number(
+        &self,
+        req: TemplateNumberReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<i32, AppError> {
+        if req.number <= 0 {
+            return Err(AppError::validation_error("增加的库存数量必须大于0"));
+        }
+
+        let dao = template_dao();
+        let txn = app_state.database.begin().await?;
+
+        let template = dao
+            .find_by_id_for_update(&txn, req.coupon_template_id)
+            .await?;
+        let template = ensure_owned(template, req.coupon_template_id)?;
+        if !template.is_active() {
+            return Err(AppError::validation_error("优惠券模板已结束，无法增加库存"));
+        }
+        let new_stock = template
+            .stock
+            .checked_add(req.number)
+            .ok_or_else(|| AppError::validation_error("增加后的库存超出上限"))?;
+
+        let rows = dao.increase_stock(&txn, &template, req.number).await?;
+        if rows == 0 {
+            return Err(AppError::internal_error(
+                "增加优惠券模板库存失败，请稍后重试",
+            ));
+        }
+        let modified = template::Model {
+            stock: new_stock,
+            ..template.clone()
+        };
+        template_log::record(
+            &txn,
+            format!("增加库存：{}", req.number),
+            Some(&template),
+            &modified,
+        )
+        .await?;
+        txn.commit().await?;
+
+        info!(
+            "增加优惠券模板库存成功, 模板ID: {}, 增加数量: {}, 当前库存: {}",
+            template.id, req.number, new_stock
+        );
+        Ok(new_stock)
+    }
+
+    /// 提前结束优惠券模板
+    ///
+    /// 将生效中的模板修改为已结束，之后不能再领取或发放；模板已结束时直接返回成功
+    ///
+    /// # 参数
+    /// * `req` - 模板ID请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<(), AppError>` - 成功时返回空，模板不存在或不属于当前店铺时返回错误
+    async fn terminate_template(
+        &self,
+        req: TemplateIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError> {
+        let dao = template_dao();
+        let txn = app_state.database.begin().await?;
+
+        let template = dao
+            .find_by_id_for_update(&txn, req.coupon_template_id)
+            .await?;
+        let template = ensure_owned(template, req.coupon_template_id)?;
+        if !template.is_active() {
+            info!("优惠券模板已结束, 无需重复操作, 模板ID: {}", template.id);
+            return Ok(());
+        }
+
+        dao.terminate(&txn, &template).await?;
+        let modified = template::Model {
+            status: CouponStatus::Ended,
+            ..template.clone()
+        };
+        template_log::record(&txn, "结束优惠券模板", Some(&template), &modified).await?;
+        txn.commit().await?;
+
+        info!(
+            "结束优惠券模板成功, 模板ID: {}, 名称: {}",
+            template.id, template.name
+        );
+        Ok(())
+    }
+
+    /// 逻辑删除优惠券模板
+    ///
+    /// 删除后模板不再出现在查询结果中，可在保留期内恢复
+    ///
+    /// # 参数
+    /// * `req` - 模板ID请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<(), AppError>` - 成功时返回空，模板不存在或不属于当前店铺时返回错误
+    async fn delete_template(
+        &self,
+        req: TemplateIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError> {
+        let dao = template_dao();
+        let txn = app_state.database.begin().await?;
+
+        let template = dao
+            .find_by_id_for_update(&txn, req.coupon_template_id)
+            .await?;
+        let template = ensure_owned(template, req.coupon_template_id)?;
+
+        dao.soft_delete(&txn, &template).await?;
+        let modified = template::Model {
+            del_flag: DELETED,
+            ..template.clone()
+        };
+        template_log::record(&txn, "删除优惠券模板", Some(&template), &modified).await?;
+        txn.commit().await?;
+
+        info!(
+            "删除优惠券模板成功, 模板ID: {}, 名称: {}",
+            template.id, template.name
+        );
+        Ok(())
+    }
+
+    /// 恢复已逻辑删除的优惠券模板
+    ///
+    /// 只能恢复删除时间在保留期内的模板，模板未被删除时直接返回成功
+    ///
+    /// # 参数
+    /// * `req` - 模板ID请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<(), AppError>` - 成功时返回空，超过保留期或模板不属于当前店铺时返回错误
+    async fn restore_template(
+        &self,
+        req: TemplateIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<(), AppError> {
+        let dao = template_dao();
+        let txn = app_state.database.begin().await?;
+
+        let template = dao
+            .find_by_id_with_deleted_for_update(&txn, req.coupon_template_id)
+            .await?;
+        let template = ensure_owned(template, req.coupon_template_id)?;
+        if template.del_flag == NOT_DELETED {
+            info!("优惠券模板未被删除, 无需恢复, 模板ID: {}", template.id);
+            return Ok(());
+        }
+
+        // 记录删除时间之前删除的模板没有删除时间，此时删除时的修改时间即删除时间
+        let deadline = Utc::now() - Duration::days(RESTORE_RETENTION_DAYS);
+        if template
+            .delete_time
+            .or(template.update_time)
+            .is_none_or(|deleted_at| deleted_at < deadline)
+        {
+            return Err(AppError::validation_error(format!(
+                "优惠券模板删除已超过 {} 天，无法恢复",
+                RESTORE_RETENTION_DAYS
+            )));
+        }
+
+        dao.restore(&txn, &template).await?;
+        let modified = template::Model {
+            del_flag: NOT_DELETED,
+            delete_time: None,
+            ..template.clone()
+        };
+        template_log::record(&txn, "恢复优惠券模板", Some(&template), &modified).await?;
+        txn.commit().await?;
+
+        info!(
+            "恢复优惠券模板成功, 模板ID: {}, 名称: {}",
+            template.id, template.name
+        );
+        Ok(())
+    }
+
+    /// 修改优惠券模板
+    ///
+    /// 请求中的修改时间必须与数据库一致，否则说明模板已被其他人修改，返回冲突错误。
+    /// 已有用户领取后，只允许修改名称、延长结束时间和规则中的说明文字。
+    /// 待审核的模板不能修改，平台券修改后回到草稿状态，需要重新提交审核
+    ///
+    /// # 参数
+    /// * `req` - 优惠券模板修改请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<template::Model, AppError>` - 成功时返回修改后的模板，其修改时间为新的版本号
+    async fn update_template(
+        &self,
+        req: TemplateUpdateReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError> {
+        req.validate()?;
+
+        let dao = template_dao();
+        let txn = app_state.database.begin().await?;
+
+        let template = dao
+            .find_by_id_for_update(&txn, req.coupon_template_id)
+            .await?;
+        let template = ensure_owned(template, req.coupon_template_id)?;
+        if !template.is_active() {
+            return Err(AppError::validation_error("已结束的优惠券模板不能修改"));
+        }
+        if template.audit_status == AuditStatus::PendingReview {
+            return Err(AppError::validation_error("审核中的优惠券模板不能修改"));
+        }
+        if req.update_time != template.update_time {
+            return Err(AppError::conflict("优惠券模板已被其他人修改，请刷新后重试"));
+        }
+
+        let mut modified = req.apply_to(&template)?;
+        if template.source.requires_audit() {
+            // 修改后的内容未经审核，需要重新提交审核
+            modified.audit_status = AuditStatus::Draft;
+            modified.reviewer_id = None;
+            modified.audit_remark = None;
+            modified.audit_time = None;
+        }
+        let issued = user_coupon_dao()
+            .count_by_template(&txn, template.id)
+            .await?;
+        if issued > 0 {
+            check_issued_changes(&template, &modified)?;
+        }
+
+        modified.update_time = template.next_version();
+
+        let rows = dao
+            .update_with_version(&txn, &modified, template.update_time)
+            .await?;
+        if rows == 0 {
+            return Err(AppError::conflict("优惠券模板已被其他人修改，请刷新后重试"));
+        }
+        template_log::record(&txn, "修改优惠券模板", Some(&template), &modified).await?;
+        txn.commit().await?;
+
+        info!(
+            "修改优惠券模板成功, 模板ID: {}, 名称: {}, 已发放: {}",
+            modified.id, modified.name, issued
+        );
+        Ok(modified)
+    }
+
+    /// 复制优惠券模板
+    ///
+    /// 以当前店铺的源模板为基础创建新模板，可以覆盖名称、有效期和库存，
+    /// 操作日志中会记录源模板ID
+    ///
+    /// # 参数
+    /// * `id` - 源优惠券模板ID
+    /// * `req` - 复制请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<i64, AppError>` - 成功时返回新模板ID，失败时返回错误
+    async fn copy_template(
+        &self,
+        id: i64,
+        req: TemplateCopyReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<i64, AppError> {
+        let dao = template_dao();
+        let source = dao.find_by_id(&app_state.database, id).await?;
+        let source = ensure_owned(source, id)?;
+        let copied = req.copy_from(&source)?;
+
+        let txn = app_state.database.begin().await?;
+        let created_model = dao.create(&txn, &copied).await?;
+        template_log::record(
+            &txn,
+            format!(
+                "复制优惠券模板：{}，源模板ID：{}",
+                created_model.name, source.id
+            ),
+            None,
+            &created_model,
+        )
+        .await?;
+        txn.commit().await?;
+
+        info!(
+            "复制优惠券模板成功, 源模板ID: {}, 新模板ID: {}",
+            source.id, created_model.id
+        );
+        Ok(created_model.id)
+    }
+
+    /// 将已过有效期但仍生效中的模板自动修改为已结束，由定时任务调用
+    ///
+    /// 每批在单独的事务中锁定、修改并记录操作日志，直到没有需要处理的模板。
+    /// 锁定时跳过其他实例正在处理的行，多个实例同时执行时不会重复处理
+    ///
+    /// # 参数
+    /// * `batch_size` - 每个事务中处理的模板数量
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<u64, AppError>` - 成功时返回结束的模板数量，失败时返回错误
+    async fn end_expired_templates(
+        &self,
+        batch_size: u64,
+        app_state: Data<AppState>,
+    ) -> Result<u64, AppError> {
+        let dao = template_dao();
+        let mut ended = 0;
+        loop {
+            let txn = app_state.database.begin().await?;
+            let templates = dao.lock_expired(&txn, Utc::now(), batch_size).await?;
+            if templates.is_empty() {
+                break;
+            }
+
+            for template in &templates {
+                ended += dao.terminate(&txn, template).await?;
+                let modified = template::Model {
+                    status: CouponStatus::Ended,
+                    ..template.clone()
+                };
+                template_log::record_with_operator(
+                    &txn,
+                    None,
+                    "有效期已结束，自动结束优惠券模板",
+                    Some(template),
+                    &modified,
+                )
+                .await?;
+            }
+            txn.commit().await?;
+
+            if (templates.len() as u64) < batch_size {
+                break;
             }
         }
+        Ok(ended)
+    }
+}
+
+/// 校验已有用户领取的模板只做了安全的修改
+///
+/// 优惠类型、优惠对象、商品、开始时间和规则中的数值都不能修改，结束时间只能延长
+fn check_issued_changes(
+    original: &template::Model,
+    modified: &template::Model,
+) -> Result<(), AppError> {
+    const REASON: &str = "已有用户领取，不能修改";
+    let mut v = Validator::new();
+    v.check("type", modified.r#type == original.r#type, REASON)
+        .check("target", modified.target == original.target, REASON)
+        .check("goods", modified.goods == original.goods, REASON)
+        .check(
+            "validStartTime",
+            modified.valid_start_time == original.valid_start_time,
+            REASON,
+        )
+        .check(
+            "validEndTime",
+            modified.valid_end_time >= original.valid_end_time,
+            "已有用户领取，有效期只能延长",
+        )
+        .check(
+            "receiveRule",
+            receive_rule_terms(original) == receive_rule_terms(modified),
+            "已有用户领取，只能修改使用说明",
+        )
+        .check(
+            "consumeRule",
+            consume_rule_terms(original) == consume_rule_terms(modified),
+            "已有用户领取，只能修改使用说明",
+        );
+    v.finish()
+}
+
+/// 去掉说明文字后的领取规则
+fn receive_rule_terms(model: &template::Model) -> Option<ReceiveRule> {
+    let rule = ReceiveRule::from_json(model.receive_rule.as_ref()?).ok()?;
+    Some(ReceiveRule {
+        usage_instructions: None,
+        ..rule
+    })
+}
+
+/// 去掉说明文字后的消耗规则
+fn consume_rule_terms(model: &template::Model) -> Option<ConsumeRule> {
+    let rule = ConsumeRule::from_json(model.consume_rule.as_ref()?).ok()?;
+    Some(ConsumeRule {
+        explanation: None,
+        ..rule
+    })
+}
+
+/// 校验模板存在且属于当前店铺
+///
+/// 模板不存在时返回未找到，属于其他店铺时返回禁止访问
+pub(crate) fn ensure_owned(
+    template: Option<template::Model>,
+    id: i64,
+) -> Result<template::Model, AppError> {
+    match template {
+        Some(model) if model.shop_number == SHOP_NUMBER => Ok(model), //TODO: 需要实现用户登录模块
+        Some(_) => Err(AppError::forbidden(format!("无权操作优惠券模板: {}", id))),
+        None => Err(AppError::not_found("优惠券模板", id)),
     }
 }
 
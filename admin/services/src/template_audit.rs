@@ -0,0 +1,237 @@
+use crate::auth::OPERATOR_ID;
+use crate::dto::template_req::{TemplateApproveReqDto, TemplateIdReqDto, TemplateRejectReqDto};
+use crate::template::ensure_owned;
+use crate::template_log;
+use crate::validation::Validate;
+use crate::AppState;
+use actix_web::web::Data;
+use chrono::Utc;
+use common::app_error::AppError;
+use data::dao::template::template_dao;
+use data::entity::template;
+use data::enums::AuditStatus;
+use log::info;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{DatabaseTransaction, TransactionTrait};
+
+#[async_trait]
+pub trait TemplateAuditService: Send + Sync {
+    async fn submit_template(
+        &self,
+        req: TemplateIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError>;
+
+    async fn approve_template(
+        &self,
+        req: TemplateApproveReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError>;
+
+    async fn reject_template(
+        &self,
+        req: TemplateRejectReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError>;
+}
+
+pub struct TemplateAuditServiceImpl;
+
+#[async_trait]
+impl TemplateAuditService for TemplateAuditServiceImpl {
+    /// 提交优惠券模板审核
+    ///
+    /// 只有需要审核的平台券可以提交，草稿和被驳回的模板提交后进入待审核状态
+    ///
+    /// # 参数
+    /// * `req` - 模板ID请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<template::Model, AppError>` - 成功时返回提交后的模板，失败时返回错误
+    async fn submit_template(
+        &self,
+        req: TemplateIdReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError> {
+        let txn = app_state.database.begin().await?;
+        let template = template_dao()
+            .find_by_id_for_update(&txn, req.coupon_template_id)
+            .await?;
+        let template = ensure_owned(template, req.coupon_template_id)?;
+        if !template.source.requires_audit() {
+            return Err(AppError::validation_error("店铺券无需审核"));
+        }
+        if !template.is_active() {
+            return Err(AppError::validation_error("已结束的优惠券模板不能提交审核"));
+        }
+
+        let modified = template::Model {
+            audit_status: AuditStatus::PendingReview,
+            reviewer_id: None,
+            audit_remark: None,
+            audit_time: None,
+            update_time: template.next_version(),
+            ..template.clone()
+        };
+        transit(
+            &txn,
+            &template,
+            &modified,
+            &[AuditStatus::Draft, AuditStatus::Rejected],
+            OPERATOR_ID, //TODO: 需要实现用户登录模块
+            "提交审核",
+            "提交审核".to_string(),
+        )
+        .await?;
+        txn.commit().await?;
+
+        info!("优惠券模板已提交审核, 模板ID: {}", modified.id);
+        Ok(modified)
+    }
+
+    /// 审核通过优惠券模板
+    ///
+    /// 只有待审核的模板可以审核通过，通过后模板可以被领取和发放
+    ///
+    /// # 参数
+    /// * `req` - 审核通过请求DTO，包含审核人ID
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<template::Model, AppError>` - 成功时返回审核后的模板，失败时返回错误
+    async fn approve_template(
+        &self,
+        req: TemplateApproveReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError> {
+        req.validate()?;
+
+        let txn = app_state.database.begin().await?;
+        let template = find_for_review(&txn, req.coupon_template_id).await?;
+        let modified = template::Model {
+            audit_status: AuditStatus::Approved,
+            reviewer_id: Some(req.reviewer_id),
+            audit_remark: req.remark.clone(),
+            audit_time: Some(Utc::now()),
+            update_time: template.next_version(),
+            ..template.clone()
+        };
+        transit(
+            &txn,
+            &template,
+            &modified,
+            &[AuditStatus::PendingReview],
+            req.reviewer_id,
+            "审核通过",
+            format!("审核通过，审核人：{}", req.reviewer_id),
+        )
+        .await?;
+        txn.commit().await?;
+
+        info!(
+            "优惠券模板审核通过, 模板ID: {}, 审核人: {}",
+            modified.id, req.reviewer_id
+        );
+        Ok(modified)
+    }
+
+    /// 驳回优惠券模板
+    ///
+    /// 只有待审核的模板可以驳回，驳回后可以修改并重新提交审核
+    ///
+    /// # 参数
+    /// * `req` - 驳回请求DTO，包含审核人ID和驳回原因
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<template::Model, AppError>` - 成功时返回驳回后的模板，失败时返回错误
+    async fn reject_template(
+        &self,
+        req: TemplateRejectReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<template::Model, AppError> {
+        req.validate()?;
+
+        let txn = app_state.database.begin().await?;
+        let template = find_for_review(&txn, req.coupon_template_id).await?;
+        let reason = req.reason.trim().to_string();
+        let modified = template::Model {
+            audit_status: AuditStatus::Rejected,
+            reviewer_id: Some(req.reviewer_id),
+            audit_remark: Some(reason.clone()),
+            audit_time: Some(Utc::now()),
+            update_time: template.next_version(),
+            ..template.clone()
+        };
+        transit(
+            &txn,
+            &template,
+            &modified,
+            &[AuditStatus::PendingReview],
+            req.reviewer_id,
+            "驳回",
+            format!("审核驳回，审核人：{}，原因：{}", req.reviewer_id, reason),
+        )
+        .await?;
+        txn.commit().await?;
+
+        info!(
+            "优惠券模板审核驳回, 模板ID: {}, 审核人: {}, 原因: {}",
+            modified.id, req.reviewer_id, reason
+        );
+        Ok(modified)
+    }
+}
+
+/// 查询并锁定待审核的模板
+///
+/// 审核由平台审核人完成，不限制模板所属店铺
+async fn find_for_review(txn: &DatabaseTransaction, id: i64) -> Result<template::Model, AppError> {
+    template_dao()
+        .find_by_id_for_update(txn, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("优惠券模板", id))
+}
+
+/// 将模板的审核状态从 `allowed` 中的某个状态修改为 `modified` 中的状态，并以 `operator_id` 记录操作日志
+async fn transit(
+    txn: &DatabaseTransaction,
+    template: &template::Model,
+    modified: &template::Model,
+    allowed: &[AuditStatus],
+    operator_id: i64,
+    action: &str,
+    operation_log: String,
+) -> Result<(), AppError> {
+    if !allowed.contains(&template.audit_status) {
+        return Err(AppError::validation_error(format!(
+            "{}状态的优惠券模板不能{}",
+            template.audit_status.label(),
+            action
+        )));
+    }
+
+    let rows = template_dao()
+        .update_audit(txn, modified, template.audit_status.clone())
+        .await?;
+    if rows == 0 {
+        return Err(AppError::conflict("优惠券模板审核状态已变化，请刷新后重试"));
+    }
+    template_log::record_with_operator(
+        txn,
+        Some(operator_id),
+        operation_log,
+        Some(template),
+        modified,
+    )
+    .await
+}
+
+static TEMPLATE_AUDIT_SERVICE: Lazy<TemplateAuditServiceImpl> =
+    Lazy::new(|| TemplateAuditServiceImpl);
+
+pub fn template_audit_service() -> &'static dyn TemplateAuditService {
+    &*TEMPLATE_AUDIT_SERVICE
+}
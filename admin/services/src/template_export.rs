@@ -0,0 +1,338 @@
+use crate::auth::SHOP_NUMBER;
+use crate::dto::template_req::TemplatePageQueryReqDto;
+use crate::AppState;
+use actix_web::web::{self, Bytes, Data};
+use chrono::Utc;
+use common::app_error::AppError;
+use common::datetime::{format_gmt8, gmt8_offset};
+use data::dao::template::{template_dao, TemplateFilter};
+use data::entity::template;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use log::error;
+use once_cell::sync::Lazy;
+use rust_xlsxwriter::{Workbook, XlsxError};
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+
+/// 每批从数据库读取的记录数
+const BATCH_SIZE: u64 = 500;
+/// 读取 XLSX 临时文件时每块的字节数
+const CHUNK_SIZE: usize = 64 * 1024;
+/// CSV 文件开头的 UTF-8 BOM，保证 Excel 打开时中文不乱码
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// 导出文件的表头
+const HEADERS: [&str; 15] = [
+    "优惠券模板ID",
+    "优惠券名称",
+    "优惠券来源",
+    "优惠对象",
+    "优惠商品编码",
+    "优惠类型",
+    "有效期开始时间",
+    "有效期结束时间",
+    "库存",
+    "领取规则",
+    "消耗规则",
+    "优惠券状态",
+    "审核状态",
+    "创建时间",
+    "修改时间",
+];
+/// 库存所在的列，XLSX 中按数字写入，其余列按文本写入
+const STOCK_COLUMN: usize = 8;
+
+/// 导出文件格式
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    /// 根据 `Accept` 请求头选择导出格式，无法识别时导出 CSV
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        match accept {
+            Some(accept) if accept.contains(XLSX_CONTENT_TYPE) => ExportFormat::Xlsx,
+            _ => ExportFormat::Csv,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => CSV_CONTENT_TYPE,
+            ExportFormat::Xlsx => XLSX_CONTENT_TYPE,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// 导出文件，`body` 以流的形式逐块输出文件内容
+pub struct ExportFile {
+    pub file_name: String,
+    pub content_type: &'static str,
+    pub body: BoxStream<'static, Result<Bytes, AppError>>,
+}
+
+#[async_trait]
+pub trait TemplateExportService: Send + Sync {
+    async fn export_templates(
+        &self,
+        req: TemplatePageQueryReqDto,
+        format: ExportFormat,
+        app_state: Data<AppState>,
+    ) -> Result<ExportFile, AppError>;
+}
+
+pub struct TemplateExportServiceImpl;
+
+#[async_trait]
+impl TemplateExportService for TemplateExportServiceImpl {
+    /// 导出符合查询条件的全部优惠券模板
+    ///
+    /// 查询条件与分页查询相同，分页参数会被忽略。数据按 ID 游标分批读取：
+    /// CSV 每读取一批就输出一批；XLSX 需要在写完后才能打包，
+    /// 因此先以常量内存模式写入临时文件，再分块输出文件内容
+    ///
+    /// # 参数
+    /// * `req` - 查询条件
+    /// * `format` - 导出文件格式
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<ExportFile, AppError>` - 成功时返回导出文件，失败时返回错误
+    async fn export_templates(
+        &self,
+        req: TemplatePageQueryReqDto,
+        format: ExportFormat,
+        app_state: Data<AppState>,
+    ) -> Result<ExportFile, AppError> {
+        let filter = req.to_filter(SHOP_NUMBER); //TODO: 需要实现用户登录模块
+        let database = app_state.database.clone();
+
+        // 先读取第一批数据，数据库不可用等错误可以在开始输出前以普通错误响应返回
+        let first_batch = fetch_batch(&database, &filter, None).await?;
+
+        let body = match format {
+            ExportFormat::Csv => csv_stream(ExportCursor {
+                database,
+                filter,
+                before_id: None,
+                prefetched: Some(first_batch),
+                finished: false,
+            }),
+            ExportFormat::Xlsx => {
+                let file = write_xlsx(&database, &filter, first_batch).await?;
+                file_stream(file)
+            }
+        };
+
+        let file_name = format!(
+            "coupon-templates-{}.{}",
+            Utc::now()
+                .with_timezone(&gmt8_offset())
+                .format("%Y%m%d%H%M%S"),
+            format.extension()
+        );
+        Ok(ExportFile {
+            file_name,
+            content_type: format.content_type(),
+            body,
+        })
+    }
+}
+
+/// 分批读取优惠券模板的游标
+struct ExportCursor {
+    database: Arc<DatabaseConnection>,
+    filter: TemplateFilter,
+    /// 上一批最后一条记录的 ID
+    before_id: Option<i64>,
+    /// 已经读取、尚未输出的一批数据
+    prefetched: Option<Vec<template::Model>>,
+    finished: bool,
+}
+
+impl ExportCursor {
+    /// 读取下一批数据，全部读取完毕时返回 `None`
+    async fn next_batch(&mut self) -> Result<Option<Vec<template::Model>>, AppError> {
+        if self.finished {
+            return Ok(None);
+        }
+        let batch = match self.prefetched.take() {
+            Some(batch) => batch,
+            None => fetch_batch(&self.database, &self.filter, self.before_id).await?,
+        };
+        self.finished = (batch.len() as u64) < BATCH_SIZE;
+        if let Some(last) = batch.last() {
+            self.before_id = Some(last.id);
+        }
+        Ok(Some(batch))
+    }
+}
+
+async fn fetch_batch(
+    database: &DatabaseConnection,
+    filter: &TemplateFilter,
+    before_id: Option<i64>,
+) -> Result<Vec<template::Model>, AppError> {
+    template_dao()
+        .list_before(database, filter, before_id, BATCH_SIZE)
+        .await
+        .map_err(|err| {
+            error!("导出优惠券模板时查询失败, 错误: {}", err);
+            AppError::from(err)
+        })
+}
+
+/// 每读取一批数据就输出一段 CSV，第一段包含 BOM 和表头
+fn csv_stream(cursor: ExportCursor) -> BoxStream<'static, Result<Bytes, AppError>> {
+    stream::try_unfold((cursor, true), |(mut cursor, first)| async move {
+        let Some(batch) = cursor.next_batch().await? else {
+            return Ok(None);
+        };
+
+        let mut buffer = Vec::new();
+        if first {
+            buffer.extend_from_slice(UTF8_BOM);
+        }
+        let mut writer = csv::Writer::from_writer(buffer);
+        if first {
+            writer.write_record(HEADERS).map_err(csv_error)?;
+        }
+        for model in &batch {
+            writer.write_record(row(model)).map_err(csv_error)?;
+        }
+        let buffer = writer
+            .into_inner()
+            .map_err(|err| csv_error(err.into_error()))?;
+
+        Ok(Some((Bytes::from(buffer), (cursor, false))))
+    })
+    .boxed()
+}
+
+/// 以常量内存模式写入 XLSX，每行写入后即落盘，返回已定位到开头的临时文件
+///
+/// 最后生成 XLSX 文件需要压缩全部数据，在阻塞线程池中执行
+async fn write_xlsx(
+    database: &Arc<DatabaseConnection>,
+    filter: &TemplateFilter,
+    first_batch: Vec<template::Model>,
+) -> Result<File, AppError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet_with_constant_memory();
+    worksheet.set_name("优惠券模板").map_err(xlsx_error)?;
+    for (col, header) in HEADERS.iter().enumerate() {
+        worksheet
+            .write_string(0, col as u16, *header)
+            .map_err(xlsx_error)?;
+    }
+
+    let mut cursor = ExportCursor {
+        database: database.clone(),
+        filter: filter.clone(),
+        before_id: None,
+        prefetched: Some(first_batch),
+        finished: false,
+    };
+    let mut row_num = 0u32;
+    while let Some(batch) = cursor.next_batch().await? {
+        for model in &batch {
+            row_num += 1;
+            for (col, value) in row(model).into_iter().enumerate() {
+                if col == STOCK_COLUMN {
+                    worksheet.write_number(row_num, col as u16, model.stock)
+                } else {
+                    worksheet.write_string(row_num, col as u16, value)
+                }
+                .map_err(xlsx_error)?;
+            }
+        }
+    }
+
+    web::block(move || {
+        let mut file = tempfile::tempfile()?;
+        workbook.save_to_writer(&mut file).map_err(xlsx_error)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(file)
+    })
+    .await
+    .map_err(AppError::internal_error)?
+}
+
+/// 将文件内容按块异步读取输出
+fn file_stream(file: File) -> BoxStream<'static, Result<Bytes, AppError>> {
+    let file = tokio::fs::File::from_std(file);
+    stream::try_unfold(file, |mut file| async move {
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.truncate(read);
+        Ok(Some((Bytes::from(buffer), file)))
+    })
+    .boxed()
+}
+
+/// 将模板转换为导出的一行，枚举显示中文名称，时间显示为 GMT+8
+fn row(model: &template::Model) -> [String; 15] {
+    [
+        model.id.to_string(),
+        model.name.clone(),
+        model.source.label().to_string(),
+        model.target.label().to_string(),
+        model.goods.clone(),
+        model.r#type.label().to_string(),
+        format_time(&model.valid_start_time),
+        format_time(&model.valid_end_time),
+        model.stock.to_string(),
+        format_json(&model.receive_rule),
+        format_json(&model.consume_rule),
+        model.status.label().to_string(),
+        model.audit_status.label().to_string(),
+        format_time(&model.create_time),
+        format_time(&model.update_time),
+    ]
+}
+
+fn format_time(time: &Option<chrono::DateTime<Utc>>) -> String {
+    time.as_ref().map(format_gmt8).unwrap_or_default()
+}
+
+fn format_json(value: &Option<serde_json::Value>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn csv_error(err: impl std::fmt::Display) -> AppError {
+    error!("生成CSV失败: {}", err);
+    AppError::internal_error(format!("生成CSV失败: {}", err))
+}
+
+fn xlsx_error(err: XlsxError) -> AppError {
+    error!("生成Excel失败: {}", err);
+    AppError::internal_error(format!("生成Excel失败: {}", err))
+}
+
+static TEMPLATE_EXPORT_SERVICE: Lazy<TemplateExportServiceImpl> =
+    Lazy::new(|| TemplateExportServiceImpl);
+
+pub fn template_export_service() -> &'static dyn TemplateExportService {
+    &*TEMPLATE_EXPORT_SERVICE
+}
@@ -0,0 +1,524 @@
+use crate::dto::template_req::{TemplateImportReqDto, TemplateSaveReqDto};
+use crate::template_log;
+use crate::validation::Validate;
+use crate::AppState;
+use actix_web::web::Data;
+use calamine::{open_workbook_from_rs, Data as Cell, Reader, Xlsx};
+use common::app_error::{AppError, FieldError};
+use common::datetime::{parse_gmt8, FORMAT};
+use data::dao::template::template_dao;
+use data::entity::template;
+use data::enums::{CouponSource, CouponTarget, CouponType};
+use log::error;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{DatabaseTransaction, TransactionTrait};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// 单次导入的最大行数 (不含表头)
+pub const MAX_IMPORT_ROWS: usize = 1000;
+
+/// XLSX 文件 (zip 格式) 的文件头
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const UTF8_BOM: &str = "\u{feff}";
+
+// 导入文件的列名，与导出文件的表头一致，导出的文件可以直接导入
+const COLUMN_NAME: &str = "优惠券名称";
+const COLUMN_SOURCE: &str = "优惠券来源";
+const COLUMN_TARGET: &str = "优惠对象";
+const COLUMN_GOODS: &str = "优惠商品编码";
+const COLUMN_TYPE: &str = "优惠类型";
+const COLUMN_VALID_START_TIME: &str = "有效期开始时间";
+const COLUMN_VALID_END_TIME: &str = "有效期结束时间";
+const COLUMN_STOCK: &str = "库存";
+const COLUMN_RECEIVE_RULE: &str = "领取规则";
+const COLUMN_CONSUME_RULE: &str = "消耗规则";
+
+/// 必须存在的列；优惠券来源为空时按店铺券导入，优惠商品编码为空时按空字符串导入
+const REQUIRED_COLUMNS: [&str; 8] = [
+    COLUMN_NAME,
+    COLUMN_TARGET,
+    COLUMN_TYPE,
+    COLUMN_VALID_START_TIME,
+    COLUMN_VALID_END_TIME,
+    COLUMN_STOCK,
+    COLUMN_RECEIVE_RULE,
+    COLUMN_CONSUME_RULE,
+];
+
+/// 单行导入结果
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateImportRow {
+    /// 行号，与表格中的行号一致 (表头为第 1 行)
+    pub row_number: usize,
+    /// 该行是否导入成功
+    pub success: bool,
+    /// 导入成功时生成的优惠券模板ID
+    pub coupon_template_id: Option<i64>,
+    /// 失败原因
+    pub message: Option<String>,
+    /// 校验不通过的字段
+    pub errors: Vec<FieldError>,
+}
+
+impl TemplateImportRow {
+    fn failed(row_number: usize, err: &AppError) -> Self {
+        let errors = match err {
+            AppError::Validation { errors, .. } => errors.clone(),
+            _ => Vec::new(),
+        };
+        TemplateImportRow {
+            row_number,
+            success: false,
+            coupon_template_id: None,
+            message: Some(err.message().to_string()),
+            errors,
+        }
+    }
+}
+
+/// 导入结果
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateImportResult {
+    /// 数据行总数
+    pub total: usize,
+    /// 导入成功的行数
+    pub success_count: usize,
+    /// 导入失败的行数
+    pub fail_count: usize,
+    /// 是否为全部成功才导入的模式
+    pub all_or_nothing: bool,
+    /// 每一行的导入结果
+    pub rows: Vec<TemplateImportRow>,
+}
+
+#[async_trait]
+pub trait TemplateImportService: Send + Sync {
+    async fn import_templates(
+        &self,
+        file_name: Option<String>,
+        content: Vec<u8>,
+        req: TemplateImportReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<TemplateImportResult, AppError>;
+}
+
+pub struct TemplateImportServiceImpl;
+
+#[async_trait]
+impl TemplateImportService for TemplateImportServiceImpl {
+    /// 从 CSV 或 XLSX 文件批量导入优惠券模板
+    ///
+    /// 第一行为表头，按列名识别各列。每一行都按新增模板的规则校验和转换，
+    /// 校验通过的行在同一个事务中写入：默认模式下单行写入失败只回滚该行；
+    /// 全部成功才导入模式下，任意一行校验不通过都不会写入任何数据
+    ///
+    /// # 参数
+    /// * `file_name` - 上传的文件名，用于识别文件格式
+    /// * `content` - 文件内容
+    /// * `req` - 导入选项
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<TemplateImportResult, AppError>` - 成功时返回每一行的导入结果，失败时返回错误
+    async fn import_templates(
+        &self,
+        file_name: Option<String>,
+        content: Vec<u8>,
+        req: TemplateImportReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<TemplateImportResult, AppError> {
+        let table = read_table(file_name.as_deref(), &content)?;
+        let (rows, mut results) = parse_table(table)?;
+
+        if req.all_or_nothing && rows.len() < results.len() {
+            // 有任意一行校验不通过时不写入任何数据
+            for (index, row_number, _) in rows {
+                results[index] = Some(TemplateImportRow {
+                    row_number,
+                    success: false,
+                    coupon_template_id: None,
+                    message: Some("其他行校验不通过，未导入".to_string()),
+                    errors: Vec::new(),
+                });
+            }
+        } else {
+            let txn = app_state.database.begin().await?;
+            for (index, row_number, model) in rows {
+                let id = if req.all_or_nothing {
+                    insert(&txn, &model).await?
+                } else {
+                    // 使用保存点，单行写入失败时只回滚该行
+                    let savepoint = txn.begin().await?;
+                    match insert(&savepoint, &model).await {
+                        Ok(id) => {
+                            savepoint.commit().await?;
+                            id
+                        }
+                        Err(err) => {
+                            error!("导入优惠券模板失败, 行号: {}, 错误: {}", row_number, err);
+                            results[index] = Some(TemplateImportRow::failed(row_number, &err));
+                            continue;
+                        }
+                    }
+                };
+                results[index] = Some(TemplateImportRow {
+                    row_number,
+                    success: true,
+                    coupon_template_id: Some(id),
+                    message: None,
+                    errors: Vec::new(),
+                });
+            }
+            txn.commit().await?;
+        }
+
+        let rows: Vec<TemplateImportRow> = results
+            .into_iter()
+            .map(|row| row.expect("每一行都有导入结果"))
+            .collect();
+        let success_count = rows.iter().filter(|row| row.success).count();
+        Ok(TemplateImportResult {
+            total: rows.len(),
+            success_count,
+            fail_count: rows.len() - success_count,
+            all_or_nothing: req.all_or_nothing,
+            rows,
+        })
+    }
+}
+
+/// 校验通过、等待写入的行：(结果下标, 行号, 模板)
+type ValidRow = (usize, usize, template::Model);
+
+/// 校验全部数据行，返回校验通过的行，以及按行排列的结果 (校验通过的行暂为 `None`)
+fn parse_table(
+    table: Vec<Vec<String>>,
+) -> Result<(Vec<ValidRow>, Vec<Option<TemplateImportRow>>), AppError> {
+    let mut lines = table.into_iter().enumerate();
+    let Some((_, header)) = lines.next() else {
+        return Err(AppError::validation_error("导入文件为空"));
+    };
+    let columns = Columns::new(&header)?;
+
+    let mut valid = Vec::new();
+    let mut results = Vec::new();
+    for (line, cells) in lines {
+        if cells.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        if results.len() == MAX_IMPORT_ROWS {
+            return Err(AppError::validation_error(format!(
+                "单次最多导入 {} 行",
+                MAX_IMPORT_ROWS
+            )));
+        }
+
+        let row_number = line + 1;
+        match columns.to_model(&cells) {
+            Ok(model) => {
+                valid.push((results.len(), row_number, model));
+                results.push(None);
+            }
+            Err(err) => results.push(Some(TemplateImportRow::failed(row_number, &err))),
+        }
+    }
+
+    if results.is_empty() {
+        return Err(AppError::validation_error("导入文件中没有数据行"));
+    }
+    Ok((valid, results))
+}
+
+/// 在事务中写入一个模板及其操作日志，返回生成的模板ID
+async fn insert(txn: &DatabaseTransaction, model: &template::Model) -> Result<i64, AppError> {
+    let created = template_dao().create(txn, model).await?;
+    template_log::record(
+        txn,
+        format!("导入优惠券模板：{}", created.name),
+        None,
+        &created,
+    )
+    .await?;
+    Ok(created.id)
+}
+
+/// 表头中各列所在的位置
+struct Columns(HashMap<String, usize>);
+
+impl Columns {
+    fn new(header: &[String]) -> Result<Self, AppError> {
+        let columns: HashMap<String, usize> = header
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.trim().trim_start_matches(UTF8_BOM).to_string(), index))
+            .collect();
+
+        let missing: Vec<FieldError> = REQUIRED_COLUMNS
+            .iter()
+            .filter(|name| !columns.contains_key(**name))
+            .map(|name| FieldError::new(*name, "缺少该列"))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::validation_errors(missing));
+        }
+        Ok(Columns(columns))
+    }
+
+    fn get<'a>(&self, cells: &'a [String], column: &str) -> &'a str {
+        self.0
+            .get(column)
+            .and_then(|index| cells.get(*index))
+            .map(|cell| cell.trim())
+            .unwrap_or_default()
+    }
+
+    /// 将一行数据转换为模板，转换规则与新增模板相同
+    fn to_model(&self, cells: &[String]) -> Result<template::Model, AppError> {
+        let dto = self.to_dto(cells)?;
+        dto.validate()?;
+        template::Model::try_from(dto)
+    }
+
+    /// 解析一行数据，一次性返回所有无法解析的字段
+    fn to_dto(&self, cells: &[String]) -> Result<TemplateSaveReqDto, AppError> {
+        let mut errors = Vec::new();
+
+        let source = match self.get(cells, COLUMN_SOURCE) {
+            "" => Some(CouponSource::Shop),
+            value => parse_enum(&mut errors, "source", value, CouponSource::from_label),
+        };
+        let target = parse_enum(
+            &mut errors,
+            "target",
+            self.get(cells, COLUMN_TARGET),
+            CouponTarget::from_label,
+        );
+        let r#type = parse_enum(
+            &mut errors,
+            "type",
+            self.get(cells, COLUMN_TYPE),
+            CouponType::from_label,
+        );
+        let valid_start_time = parse_time(
+            &mut errors,
+            "validStartTime",
+            self.get(cells, COLUMN_VALID_START_TIME),
+        );
+        let valid_end_time = parse_time(
+            &mut errors,
+            "validEndTime",
+            self.get(cells, COLUMN_VALID_END_TIME),
+        );
+        let stock = match self.get(cells, COLUMN_STOCK).parse::<i32>() {
+            Ok(stock) => Some(stock),
+            Err(_) => {
+                errors.push(FieldError::new("stock", "必须是整数"));
+                None
+            }
+        };
+
+        match (source, target, r#type, stock) {
+            (Some(source), Some(target), Some(r#type), Some(stock)) if errors.is_empty() => {
+                Ok(TemplateSaveReqDto {
+                    name: self.get(cells, COLUMN_NAME).to_string(),
+                    shop_number: None,
+                    source,
+                    target,
+                    goods: self.get(cells, COLUMN_GOODS).to_string(),
+                    r#type,
+                    valid_start_time,
+                    valid_end_time,
+                    stock,
+                    receive_rule: self.get(cells, COLUMN_RECEIVE_RULE).to_string(),
+                    consume_rule: self.get(cells, COLUMN_CONSUME_RULE).to_string(),
+                })
+            }
+            _ => Err(AppError::validation_errors(errors)),
+        }
+    }
+}
+
+/// 解析枚举列，支持中文名称或数字编码
+fn parse_enum<T>(
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    value: &str,
+    from_label: fn(&str) -> Option<T>,
+) -> Option<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let parsed = from_label(value).or_else(|| {
+        value
+            .parse::<i32>()
+            .ok()
+            .and_then(|code| serde_json::from_value(code.into()).ok())
+    });
+    if parsed.is_none() {
+        errors.push(FieldError::new(field, format!("无法识别的值: {}", value)));
+    }
+    parsed
+}
+
+/// 解析 GMT+8 时间列，空值交给后续校验处理
+fn parse_time(
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    value: &str,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    if value.is_empty() {
+        return None;
+    }
+    match parse_gmt8(value) {
+        Ok(time) => Some(time),
+        Err(message) => {
+            errors.push(FieldError::new(field, message));
+            None
+        }
+    }
+}
+
+/// 文件名以 `.xlsx` 结尾，或没有 `.csv` 扩展名且内容为 zip 格式时，按 XLSX 文件处理
+pub(crate) fn is_xlsx(file_name: Option<&str>, content: &[u8]) -> bool {
+    match file_name.and_then(|name| name.rsplit_once('.')) {
+        Some((_, extension)) if extension.eq_ignore_ascii_case("xlsx") => true,
+        Some((_, extension)) if extension.eq_ignore_ascii_case("csv") => false,
+        _ => content.starts_with(ZIP_MAGIC),
+    }
+}
+
+/// 将上传的文件读取为字符串表格
+pub(crate) fn read_table(
+    file_name: Option<&str>,
+    content: &[u8],
+) -> Result<Vec<Vec<String>>, AppError> {
+    if is_xlsx(file_name, content) {
+        read_xlsx(content)
+    } else {
+        read_csv(content)
+    }
+}
+
+fn read_csv(content: &[u8]) -> Result<Vec<Vec<String>>, AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content);
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_string).collect())
+                .map_err(|err| AppError::validation_error(format!("CSV 文件格式错误: {}", err)))
+        })
+        .collect()
+}
+
+/// 读取 XLSX 文件的第一个工作表
+fn read_xlsx(content: &[u8]) -> Result<Vec<Vec<String>>, AppError> {
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(content))
+        .map_err(|err| AppError::validation_error(format!("Excel 文件格式错误: {}", err)))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| AppError::validation_error("Excel 文件中没有工作表"))?
+        .map_err(|err| AppError::validation_error(format!("Excel 文件格式错误: {}", err)))?;
+
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(cell_to_string).collect())
+        .collect())
+}
+
+/// 单元格转换为字符串，日期按 GMT+8 的 "yyyy-MM-dd HH:mm:ss" 格式输出
+fn cell_to_string(cell: &Cell) -> String {
+    match cell {
+        Cell::Float(value) if value.fract() == 0.0 => format!("{}", *value as i64),
+        Cell::DateTime(value) => value
+            .as_datetime()
+            .map(|naive| naive.format(FORMAT).to_string())
+            .unwrap_or_else(|| value.to_string()),
+        _ => cell.to_string(),
+    }
+}
+
+static TEMPLATE_IMPORT_SERVICE: Lazy<TemplateImportServiceImpl> =
+    Lazy::new(|| TemplateImportServiceImpl);
+
+pub fn template_import_service() -> &'static dyn TemplateImportService {
+    &*TEMPLATE_IMPORT_SERVICE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use common::datetime::format_gmt8;
+
+    fn line(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| cell.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_rows_by_header_and_reports_invalid_fields() {
+        let start = format_gmt8(&(Utc::now() + Duration::days(1)));
+        let end = format_gmt8(&(Utc::now() + Duration::days(7)));
+        let table = vec![
+            line(&[
+                COLUMN_STOCK,
+                COLUMN_NAME,
+                COLUMN_TARGET,
+                COLUMN_TYPE,
+                COLUMN_VALID_START_TIME,
+                COLUMN_VALID_END_TIME,
+                COLUMN_RECEIVE_RULE,
+                COLUMN_CONSUME_RULE,
+            ]),
+            line(&[
+                "100",
+                "满10减3",
+                "全店通用",
+                "满减券",
+                &start,
+                &end,
+                r#"{"limitPerPerson":1}"#,
+                r#"{"termsOfUse":10,"maximumDiscountAmount":3}"#,
+            ]),
+            line(&["", "", "", "", "", "", "", ""]),
+            line(&[
+                "many",
+                "折扣券",
+                "1",
+                "未知",
+                &start,
+                &end,
+                r#"{"limitPerPerson":1}"#,
+                r#"{"discountRate":0.8}"#,
+            ]),
+        ];
+
+        let (valid, results) = parse_table(table).unwrap();
+        assert_eq!(valid.len(), 1);
+        assert_eq!(valid[0].1, 2);
+        assert_eq!(valid[0].2.target, CouponTarget::StoreWide);
+        assert_eq!(valid[0].2.stock, 100);
+
+        let invalid = results[1].as_ref().unwrap();
+        assert_eq!(invalid.row_number, 4);
+        let fields: Vec<&str> = invalid.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["type", "stock"]);
+    }
+
+    #[test]
+    fn rejects_file_without_required_columns() {
+        let Err(AppError::Validation { errors, .. }) = parse_table(vec![line(&[COLUMN_NAME])])
+        else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(errors.len(), REQUIRED_COLUMNS.len() - 1);
+    }
+}
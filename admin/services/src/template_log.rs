@@ -0,0 +1,156 @@
+use crate::auth::{OPERATOR_ID, SHOP_NUMBER};
+use crate::dto::page_req::{check_page, PageReqDto};
+use crate::AppState;
+use actix_web::web::Data;
+use chrono::Utc;
+use common::app_error::AppError;
+use common::transfer::PageResult;
+use data::dao::template_log::template_log_dao;
+use data::entity::{template, template_log};
+use log::error;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::DatabaseTransaction;
+use serde_json::{Map, Value as JsonValue};
+
+/// 每次修改都会变化、不参与差异对比的字段
+const IGNORED_FIELDS: [&str; 1] = ["updateTime"];
+
+#[async_trait]
+pub trait TemplateLogService: Send + Sync {
+    async fn page_logs(
+        &self,
+        coupon_template_id: i64,
+        req: PageReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<PageResult<template_log::Model>, AppError>;
+}
+
+pub struct TemplateLogServiceImpl;
+
+#[async_trait]
+impl TemplateLogService for TemplateLogServiceImpl {
+    /// 分页查询优惠券模板操作日志
+    ///
+    /// 只返回当前店铺的日志，按操作时间倒序排列
+    ///
+    /// # 参数
+    /// * `coupon_template_id` - 优惠券模板ID
+    /// * `req` - 分页请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<PageResult<template_log::Model>, AppError>` - 成功时返回分页结果，失败时返回错误
+    async fn page_logs(
+        &self,
+        coupon_template_id: i64,
+        req: PageReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<PageResult<template_log::Model>, AppError> {
+        check_page(req.current, req.size)?;
+
+        let (records, total) = template_log_dao()
+            .page_by_template(
+                &app_state.database,
+                SHOP_NUMBER, //TODO: 需要实现用户登录模块
+                coupon_template_id,
+                req.current,
+                req.size,
+            )
+            .await
+            .map_err(|err| {
+                error!(
+                    "查询优惠券模板操作日志失败, 模板ID: {}, 错误: {}",
+                    coupon_template_id, err
+                );
+                AppError::from(err)
+            })?;
+
+        Ok(PageResult::new(req.current, req.size, total, records))
+    }
+}
+
+/// 在事务中记录一次当前用户对优惠券模板的操作
+///
+/// `original` 为空表示新建，此时记录模板的完整快照；否则只记录发生变化的字段
+pub(crate) async fn record(
+    txn: &DatabaseTransaction,
+    operation_log: impl Into<String>,
+    original: Option<&template::Model>,
+    modified: &template::Model,
+) -> Result<(), AppError> {
+    //TODO: 需要实现用户登录模块
+    record_with_operator(txn, Some(OPERATOR_ID), operation_log, original, modified).await
+}
+
+/// 在事务中记录一次优惠券模板操作，`operator_id` 为空表示由系统自动执行
+pub(crate) async fn record_with_operator(
+    txn: &DatabaseTransaction,
+    operator_id: Option<i64>,
+    operation_log: impl Into<String>,
+    original: Option<&template::Model>,
+    modified: &template::Model,
+) -> Result<(), AppError> {
+    let (original_data, modified_data) = diff(original, modified);
+
+    let log = template_log::Model {
+        id: 0,
+        shop_number: modified.shop_number,
+        coupon_template_id: modified.id,
+        operator_id,
+        operation_log: operation_log.into(),
+        original_data,
+        modified_data,
+        create_time: Some(Utc::now()),
+    };
+    template_log_dao().create(txn, &log).await?;
+    Ok(())
+}
+
+/// 对比修改前后的模板，返回 (原始数据, 修改后数据) 两个 JSON 字符串
+fn diff(
+    original: Option<&template::Model>,
+    modified: &template::Model,
+) -> (Option<String>, Option<String>) {
+    let after = snapshot(modified);
+    let Some(original) = original else {
+        return (None, Some(JsonValue::Object(after).to_string()));
+    };
+
+    let before = snapshot(original);
+    let mut original_data = Map::new();
+    let mut modified_data = Map::new();
+    for (field, value) in after {
+        let old_value = before.get(&field).cloned().unwrap_or(JsonValue::Null);
+        if old_value != value {
+            original_data.insert(field.clone(), old_value);
+            modified_data.insert(field, value);
+        }
+    }
+
+    if modified_data.is_empty() {
+        return (None, None);
+    }
+    (
+        Some(JsonValue::Object(original_data).to_string()),
+        Some(JsonValue::Object(modified_data).to_string()),
+    )
+}
+
+/// 将模板序列化为 JSON 对象，去掉不参与对比的字段
+fn snapshot(model: &template::Model) -> Map<String, JsonValue> {
+    let mut fields = match serde_json::to_value(model) {
+        Ok(JsonValue::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    for field in IGNORED_FIELDS {
+        fields.remove(field);
+    }
+    fields
+}
+
+static TEMPLATE_LOG_SERVICE: Lazy<TemplateLogServiceImpl> = Lazy::new(|| TemplateLogServiceImpl);
+
+pub fn template_log_service() -> &'static dyn TemplateLogService {
+    &*TEMPLATE_LOG_SERVICE
+}
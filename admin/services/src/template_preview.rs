@@ -0,0 +1,241 @@
+use crate::dto::template_req::{PreviewGoodsDto, TemplatePreviewReqDto};
+use crate::template::template_service;
+use crate::validation::Validate;
+use crate::AppState;
+use actix_web::web::Data;
+use common::app_error::AppError;
+use data::entity::template;
+use data::enums::{CouponTarget, CouponType};
+use data::rule::ConsumeRule;
+use once_cell::sync::Lazy;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sea_orm::prelude::async_trait::async_trait;
+use serde::Serialize;
+
+/// 金额保留的小数位数
+const AMOUNT_SCALE: u32 = 2;
+
+/// 优惠试算结果
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscountPreview {
+    /// 优惠券是否可用
+    pub applicable: bool,
+    /// 不可用的原因
+    pub reason: Option<String>,
+    /// 订单金额
+    pub order_amount: Decimal,
+    /// 参与优惠的金额：全店通用券为订单金额，商品专属券为优惠商品的金额合计
+    pub applicable_amount: Decimal,
+    /// 优惠金额
+    pub discount_amount: Decimal,
+    /// 应付金额
+    pub payable_amount: Decimal,
+}
+
+#[async_trait]
+pub trait TemplatePreviewService: Send + Sync {
+    async fn preview_discount(
+        &self,
+        req: TemplatePreviewReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<DiscountPreview, AppError>;
+}
+
+pub struct TemplatePreviewServiceImpl;
+
+#[async_trait]
+impl TemplatePreviewService for TemplatePreviewServiceImpl {
+    /// 按示例订单试算优惠券的优惠金额
+    ///
+    /// 模板可以是已保存的模板，也可以是尚未保存的模板内容，后者按新增模板的规则校验。
+    /// 试算只计算金额，不检查模板状态和有效期
+    ///
+    /// # 参数
+    /// * `req` - 优惠试算请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<DiscountPreview, AppError>` - 成功时返回试算结果，失败时返回错误
+    async fn preview_discount(
+        &self,
+        req: TemplatePreviewReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<DiscountPreview, AppError> {
+        req.validate()?;
+
+        let template = match (req.coupon_template_id, req.template) {
+            (Some(id), _) => template_service().find_template(id, app_state).await?,
+            (None, Some(dto)) => {
+                dto.validate()?;
+                template::Model::try_from(dto)?
+            }
+            (None, None) => unreachable!("请求校验保证模板ID和模板内容必须填写一个"),
+        };
+        calculate(&template, req.order_amount, &req.goods)
+    }
+}
+
+/// 按优惠类型计算优惠金额
+///
+/// * 立减券：直接减免 `maximumDiscountAmount`，填写了使用门槛时需要达到门槛
+/// * 满减券：参与优惠的金额达到 `termsOfUse` 时减免 `maximumDiscountAmount`
+/// * 折扣券：优惠金额为参与优惠的金额 × (1 - 折扣率)，四舍五入到分，
+///   不超过 `maximumDiscountAmount`
+///
+/// 优惠金额不会超过参与优惠的金额
+pub fn calculate(
+    template: &template::Model,
+    order_amount: Decimal,
+    goods: &[PreviewGoodsDto],
+) -> Result<DiscountPreview, AppError> {
+    let consume_rule = match &template.consume_rule {
+        Some(value) => ConsumeRule::from_json(value).map_err(|v| v.into_error())?,
+        None => return Err(AppError::validation_error("consumeRule: 消耗规则不能为空")),
+    };
+
+    let applicable_amount = match template.target {
+        CouponTarget::StoreWide => order_amount,
+        CouponTarget::SpecificGoods => goods
+            .iter()
+            .filter(|item| item.goods_number.trim() == template.goods)
+            .map(|item| item.amount)
+            .sum(),
+    };
+    let preview = |discount_amount: Decimal, reason: Option<String>| DiscountPreview {
+        applicable: reason.is_none(),
+        reason,
+        order_amount,
+        applicable_amount,
+        discount_amount,
+        payable_amount: order_amount - discount_amount,
+    };
+
+    if template.target == CouponTarget::SpecificGoods && applicable_amount.is_zero() {
+        return Ok(preview(
+            Decimal::ZERO,
+            Some(format!("订单中没有优惠商品: {}", template.goods)),
+        ));
+    }
+    if let Some(threshold) = consume_rule.terms_of_use {
+        if applicable_amount < threshold {
+            return Ok(preview(
+                Decimal::ZERO,
+                Some(format!("未达到使用门槛: 满{}元可用", threshold.normalize())),
+            ));
+        }
+    }
+
+    let discount_amount = match template.r#type {
+        CouponType::InstantReduction | CouponType::FullReduction => {
+            consume_rule.maximum_discount_amount.unwrap_or_default()
+        }
+        CouponType::Discount => {
+            let rate = consume_rule.discount_rate.unwrap_or(Decimal::ONE);
+            let discount = (applicable_amount * (Decimal::ONE - rate))
+                .round_dp_with_strategy(AMOUNT_SCALE, RoundingStrategy::MidpointAwayFromZero);
+            match consume_rule.maximum_discount_amount {
+                Some(cap) => discount.min(cap),
+                None => discount,
+            }
+        }
+    };
+    Ok(preview(discount_amount.min(applicable_amount), None))
+}
+
+static TEMPLATE_PREVIEW_SERVICE: Lazy<TemplatePreviewServiceImpl> =
+    Lazy::new(|| TemplatePreviewServiceImpl);
+
+pub fn template_preview_service() -> &'static dyn TemplatePreviewService {
+    &*TEMPLATE_PREVIEW_SERVICE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::template_model;
+    use rust_decimal::prelude::FromStr;
+    use serde_json::json;
+
+    fn amount(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn model(
+        r#type: CouponType,
+        target: CouponTarget,
+        consume_rule: serde_json::Value,
+    ) -> template::Model {
+        template::Model {
+            target,
+            r#type,
+            consume_rule: Some(consume_rule),
+            ..template_model()
+        }
+    }
+
+    #[test]
+    fn full_reduction_requires_threshold_to_be_reached() {
+        let template = model(
+            CouponType::FullReduction,
+            CouponTarget::StoreWide,
+            json!({"termsOfUse": 100, "maximumDiscountAmount": 15}),
+        );
+
+        let preview = calculate(&template, amount("99.99"), &[]).unwrap();
+        assert!(!preview.applicable);
+        assert_eq!(preview.payable_amount, amount("99.99"));
+
+        let preview = calculate(&template, amount("100"), &[]).unwrap();
+        assert!(preview.applicable);
+        assert_eq!(preview.discount_amount, amount("15"));
+        assert_eq!(preview.payable_amount, amount("85"));
+    }
+
+    #[test]
+    fn discount_rounds_to_cents_and_respects_cap() {
+        let template = model(
+            CouponType::Discount,
+            CouponTarget::StoreWide,
+            json!({"discountRate": 0.85}),
+        );
+        let preview = calculate(&template, amount("33.33"), &[]).unwrap();
+        assert_eq!(preview.discount_amount, amount("5.00"));
+        assert_eq!(preview.payable_amount, amount("28.33"));
+
+        let template = model(
+            CouponType::Discount,
+            CouponTarget::StoreWide,
+            json!({"discountRate": 0.5, "maximumDiscountAmount": 20}),
+        );
+        let preview = calculate(&template, amount("100"), &[]).unwrap();
+        assert_eq!(preview.discount_amount, amount("20"));
+    }
+
+    #[test]
+    fn specific_goods_only_discounts_matching_goods() {
+        let template = model(
+            CouponType::InstantReduction,
+            CouponTarget::SpecificGoods,
+            json!({"maximumDiscountAmount": 30}),
+        );
+        let goods = vec![
+            PreviewGoodsDto {
+                goods_number: "G001".to_string(),
+                amount: amount("12.5"),
+            },
+            PreviewGoodsDto {
+                goods_number: "G002".to_string(),
+                amount: amount("80"),
+            },
+        ];
+
+        let preview = calculate(&template, amount("92.5"), &goods).unwrap();
+        assert_eq!(preview.applicable_amount, amount("12.5"));
+        assert_eq!(preview.discount_amount, amount("12.5"));
+        assert_eq!(preview.payable_amount, amount("80"));
+
+        let preview = calculate(&template, amount("80"), &goods[1..]).unwrap();
+        assert!(!preview.applicable);
+    }
+}
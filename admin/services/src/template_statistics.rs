@@ -0,0 +1,224 @@
+use crate::auth::SHOP_NUMBER;
+use crate::cache::TtlCache;
+use crate::template::template_service;
+use crate::AppState;
+use actix_web::web::Data;
+use chrono::{DateTime, Utc};
+use common::app_error::AppError;
+use common::datetime::serde_option_datetime_utc_as_gmt8_string;
+use data::dao::coupon_settlement::coupon_settlement_dao;
+use data::dao::template::template_dao;
+use data::dao::user_coupon::{user_coupon_dao, CouponScope};
+use data::enums::UserCouponStatus;
+use log::error;
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::time::Duration;
+
+/// 统计结果的缓存时间，聚合查询开销较大，短时间内的重复查询直接返回缓存
+const STATISTICS_CACHE_TTL: Duration = Duration::from_secs(60);
+/// 核销率保留的小数位数
+const RATE_SCALE: u32 = 4;
+
+static TEMPLATE_STATISTICS_CACHE: Lazy<TtlCache<i64, CouponStatistics>> =
+    Lazy::new(|| TtlCache::new(STATISTICS_CACHE_TTL));
+static SHOP_STATISTICS_CACHE: Lazy<TtlCache<i64, ShopStatistics>> =
+    Lazy::new(|| TtlCache::new(STATISTICS_CACHE_TTL));
+
+/// 优惠券发放和核销统计
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CouponStatistics {
+    /// 已发放数量
+    pub issued_count: i64,
+    /// 未使用数量
+    pub unused_count: i64,
+    /// 锁定数量
+    pub locked_count: i64,
+    /// 已使用数量
+    pub used_count: i64,
+    /// 已过期数量
+    pub expired_count: i64,
+    /// 已撤回数量
+    pub revoked_count: i64,
+    /// 核销率：已使用数量 / 已发放数量
+    pub redemption_rate: Decimal,
+    /// 已支付订单的优惠金额合计
+    pub total_discount_amount: Decimal,
+    /// 统计时间，结果可能来自缓存 (JSON 中为 GMT+8 字符串, Rust 内部为 UTC)
+    #[serde(with = "serde_option_datetime_utc_as_gmt8_string")]
+    pub statistics_time: Option<DateTime<Utc>>,
+}
+
+impl CouponStatistics {
+    /// 根据按状态分组的数量和优惠金额合计生成统计结果
+    fn new(counts: &[(UserCouponStatus, i64)], total_discount_amount: Decimal) -> Self {
+        let mut statistics = CouponStatistics {
+            total_discount_amount,
+            statistics_time: Some(Utc::now()),
+            ..Default::default()
+        };
+        for (status, count) in counts {
+            statistics.issued_count += count;
+            match status {
+                UserCouponStatus::Unused => statistics.unused_count += count,
+                UserCouponStatus::Locked => statistics.locked_count += count,
+                UserCouponStatus::Used => statistics.used_count += count,
+                UserCouponStatus::Expired => statistics.expired_count += count,
+                UserCouponStatus::Revoked => statistics.revoked_count += count,
+            }
+        }
+        if statistics.issued_count > 0 {
+            statistics.redemption_rate = (Decimal::from(statistics.used_count)
+                / Decimal::from(statistics.issued_count))
+            .round_dp(RATE_SCALE);
+        }
+        statistics
+    }
+}
+
+/// 店铺全部优惠券模板的汇总统计
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShopStatistics {
+    /// 店铺编号
+    pub shop_number: i64,
+    /// 未删除的优惠券模板数量
+    pub template_count: u64,
+    /// 全部模板的发放和核销统计
+    #[serde(flatten)]
+    pub coupons: CouponStatistics,
+}
+
+#[async_trait]
+pub trait TemplateStatisticsService: Send + Sync {
+    async fn template_statistics(
+        &self,
+        coupon_template_id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<CouponStatistics, AppError>;
+
+    async fn shop_statistics(&self, app_state: Data<AppState>) -> Result<ShopStatistics, AppError>;
+}
+
+pub struct TemplateStatisticsServiceImpl;
+
+#[async_trait]
+impl TemplateStatisticsService for TemplateStatisticsServiceImpl {
+    /// 查询单个优惠券模板的发放和核销统计
+    ///
+    /// 统计结果缓存一分钟，缓存期间的新发放或核销不会立即体现
+    ///
+    /// # 参数
+    /// * `coupon_template_id` - 优惠券模板ID
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<CouponStatistics, AppError>` - 成功时返回统计结果，模板不存在或不属于当前店铺时返回错误
+    async fn template_statistics(
+        &self,
+        coupon_template_id: i64,
+        app_state: Data<AppState>,
+    ) -> Result<CouponStatistics, AppError> {
+        // 先确认模板属于当前店铺，再读取缓存，避免通过缓存访问其他店铺的数据
+        template_service()
+            .find_template(coupon_template_id, app_state.clone())
+            .await?;
+
+        if let Some(statistics) = TEMPLATE_STATISTICS_CACHE.get(&coupon_template_id) {
+            return Ok(statistics);
+        }
+        let statistics = aggregate(
+            &app_state.database,
+            CouponScope::Template(coupon_template_id),
+        )
+        .await?;
+        TEMPLATE_STATISTICS_CACHE.insert(coupon_template_id, statistics.clone());
+        Ok(statistics)
+    }
+
+    /// 查询当前店铺全部优惠券模板的汇总统计
+    ///
+    /// 统计结果缓存一分钟
+    ///
+    /// # 参数
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<ShopStatistics, AppError>` - 成功时返回统计结果，失败时返回错误
+    async fn shop_statistics(&self, app_state: Data<AppState>) -> Result<ShopStatistics, AppError> {
+        let shop_number = SHOP_NUMBER; //TODO: 需要实现用户登录模块
+        if let Some(statistics) = SHOP_STATISTICS_CACHE.get(&shop_number) {
+            return Ok(statistics);
+        }
+
+        let template_count = template_dao()
+            .count_by_shop(&app_state.database, shop_number)
+            .await?;
+        let coupons = aggregate(&app_state.database, CouponScope::Shop(shop_number)).await?;
+        let statistics = ShopStatistics {
+            shop_number,
+            template_count,
+            coupons,
+        };
+        SHOP_STATISTICS_CACHE.insert(shop_number, statistics.clone());
+        Ok(statistics)
+    }
+}
+
+/// 执行聚合查询
+async fn aggregate(
+    database: &DatabaseConnection,
+    scope: CouponScope,
+) -> Result<CouponStatistics, AppError> {
+    let counts = user_coupon_dao()
+        .count_by_status(database, scope)
+        .await
+        .map_err(|err| {
+            error!("统计用户优惠券失败, 范围: {:?}, 错误: {}", scope, err);
+            AppError::from(err)
+        })?;
+    let total_discount_amount = coupon_settlement_dao()
+        .sum_paid_discount(database, scope)
+        .await
+        .map_err(|err| {
+            error!("统计优惠金额失败, 范围: {:?}, 错误: {}", scope, err);
+            AppError::from(err)
+        })?;
+
+    Ok(CouponStatistics::new(&counts, total_discount_amount))
+}
+
+static TEMPLATE_STATISTICS_SERVICE: Lazy<TemplateStatisticsServiceImpl> =
+    Lazy::new(|| TemplateStatisticsServiceImpl);
+
+pub fn template_statistics_service() -> &'static dyn TemplateStatisticsService {
+    &*TEMPLATE_STATISTICS_SERVICE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_totals_and_redemption_rate() {
+        let counts = [
+            (UserCouponStatus::Unused, 4),
+            (UserCouponStatus::Used, 2),
+            (UserCouponStatus::Revoked, 1),
+        ];
+        let statistics = CouponStatistics::new(&counts, Decimal::new(1050, 2));
+
+        assert_eq!(statistics.issued_count, 7);
+        assert_eq!(statistics.used_count, 2);
+        assert_eq!(statistics.redemption_rate, Decimal::new(2857, 4));
+        assert_eq!(statistics.total_discount_amount, Decimal::new(1050, 2));
+        assert_eq!(
+            CouponStatistics::new(&[], Decimal::ZERO).redemption_rate,
+            Decimal::ZERO
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! 单元测试共用的数据构造
+
+use data::entity::template;
+use data::soft_delete::NOT_DELETED;
+
+/// 构造一个优惠券模板，测试按需覆盖其中的字段
+pub(crate) fn template_model() -> template::Model {
+    template::Model {
+        id: 1,
+        shop_number: 1,
+        name: "测试模板".to_string(),
+        source: Default::default(),
+        target: Default::default(),
+        goods: "G001".to_string(),
+        r#type: Default::default(),
+        valid_start_time: None,
+        valid_end_time: None,
+        stock: 1,
+        receive_rule: None,
+        consume_rule: None,
+        status: Default::default(),
+        create_time: None,
+        update_time: None,
+        del_flag: NOT_DELETED,
+        delete_time: None,
+        audit_status: Default::default(),
+        reviewer_id: None,
+        audit_remark: None,
+        audit_time: None,
+    }
+}
@@ -0,0 +1,211 @@
+use crate::auth::USER_ID;
+use crate::dto::user_coupon_req::CouponClaimReqDto;
+use crate::AppState;
+use actix_web::web::Data;
+use chrono::{DateTime, Utc};
+use common::app_error::AppError;
+use common::error_code::BaseErrorCode;
+use data::dao::template::template_dao;
+use data::dao::user_coupon::user_coupon_dao;
+use data::entity::{template, user_coupon};
+use data::enums::{CouponSource, UserCouponSource, UserCouponStatus};
+use data::rule::ReceiveRule;
+use data::soft_delete::NOT_DELETED;
+use log::info;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{SqlErr, TransactionTrait};
+
+#[async_trait]
+pub trait UserCouponService: Send + Sync {
+    async fn claim(
+        &self,
+        req: CouponClaimReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<user_coupon::Model, AppError>;
+}
+
+pub struct UserCouponServiceImpl;
+
+#[async_trait]
+impl UserCouponService for UserCouponServiceImpl {
+    /// 当前用户领取优惠券
+    ///
+    /// 在一个事务中锁定模板行，校验模板生效中、审核通过且在有效期内，校验每人限领张数，
+    /// 扣减一张库存并写入下一次领取次数的用户优惠券，有效期与模板一致。同一模板的领取按模板行锁串行，
+    /// 唯一索引冲突说明同一用户的并发领取已先提交，同样视为超过限领张数
+    ///
+    /// # 参数
+    /// * `req` - 领取优惠券请求DTO
+    /// * `app_state` - 应用程序状态，包含数据库连接
+    ///
+    /// # 返回
+    /// * `Result<user_coupon::Model, AppError>` - 成功时返回领取到的用户优惠券，库存不足和超过限领张数时分别返回对应的错误码
+    async fn claim(
+        &self,
+        req: CouponClaimReqDto,
+        app_state: Data<AppState>,
+    ) -> Result<user_coupon::Model, AppError> {
+        let user_id = USER_ID; //TODO: 需要实现用户登录模块
+        let template_id = req.coupon_template_id;
+
+        let txn = app_state.database.begin().await?;
+        let template = template_dao()
+            .find_by_id_for_update(&txn, template_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("优惠券模板", template_id))?;
+        let received = user_coupon_dao()
+            .max_receive_counts(&txn, template_id, &[user_id])
+            .await?
+            .first()
+            .map_or(0, |(_, receive_count)| *receive_count);
+        let receive_count = check_claim(&template, received, Utc::now())?;
+
+        let decreased = template_dao().decrease_stock(&txn, &template, 1).await?;
+        if decreased == 0 {
+            return Err(stock_exhausted());
+        }
+        let coupon = user_coupon_dao()
+            .create(&txn, &new_user_coupon(&template, user_id, receive_count))
+            .await
+            .map_err(|err| match err.sql_err() {
+                Some(SqlErr::UniqueConstraintViolation(_)) => limit_exceeded(),
+                _ => AppError::from(err),
+            })?;
+        txn.commit().await?;
+
+        info!(
+            "用户领取优惠券成功, 用户ID: {}, 模板ID: {}, 第 {} 次领取",
+            user_id, template_id, receive_count
+        );
+        Ok(coupon)
+    }
+}
+
+/// 校验用户可以领取模板，返回本次的领取次数
+///
+/// `received` 为用户在该模板下已有的最大领取次数，没有领取过时为 0
+fn check_claim(
+    template: &template::Model,
+    received: i32,
+    now: DateTime<Utc>,
+) -> Result<i32, AppError> {
+    if !template.is_issuable() {
+        return Err(AppError::validation_error("优惠券模板未生效或未审核通过"));
+    }
+    if template.valid_start_time.is_some_and(|start| start > now) {
+        return Err(AppError::validation_error("优惠券尚未到领取时间"));
+    }
+    if template.valid_end_time.is_some_and(|end| end <= now) {
+        return Err(AppError::validation_error("优惠券已过有效期"));
+    }
+
+    let rule = template
+        .receive_rule
+        .as_ref()
+        .and_then(|value| ReceiveRule::from_json(value).ok())
+        .ok_or_else(|| AppError::validation_error("优惠券模板的领取规则无效"))?;
+    if received >= rule.limit_per_person {
+        return Err(limit_exceeded());
+    }
+    if template.stock < 1 {
+        return Err(stock_exhausted());
+    }
+    Ok(received + 1)
+}
+
+fn stock_exhausted() -> AppError {
+    AppError::client(BaseErrorCode::CouponStockExhausted, None)
+}
+
+fn limit_exceeded() -> AppError {
+    AppError::client(BaseErrorCode::CouponReceiveLimitExceeded, None)
+}
+
+/// 用户领取的优惠券，店铺券来源为店铺领取，平台券为领券中心
+fn new_user_coupon(
+    template: &template::Model,
+    user_id: i64,
+    receive_count: i32,
+) -> user_coupon::Model {
+    let now = Utc::now();
+    user_coupon::Model {
+        id: 0,
+        user_id,
+        coupon_template_id: template.id,
+        receive_time: Some(now),
+        receive_count,
+        valid_start_time: template.valid_start_time,
+        valid_end_time: template.valid_end_time,
+        use_time: None,
+        source: match template.source {
+            CouponSource::Shop => UserCouponSource::ShopReceive,
+            CouponSource::Platform => UserCouponSource::ReceiveCenter,
+        },
+        status: UserCouponStatus::Unused,
+        create_time: Some(now),
+        update_time: Some(now),
+        del_flag: NOT_DELETED,
+    }
+}
+
+static USER_COUPON_SERVICE: Lazy<UserCouponServiceImpl> = Lazy::new(|| UserCouponServiceImpl);
+
+pub fn user_coupon_service() -> &'static dyn UserCouponService {
+    &*USER_COUPON_SERVICE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::template_model;
+    use chrono::Duration;
+    use data::enums::{AuditStatus, CouponStatus};
+    use serde_json::json;
+
+    fn model(stock: i32, limit_per_person: i32) -> template::Model {
+        template::Model {
+            stock,
+            receive_rule: Some(json!({"limitPerPerson": limit_per_person})),
+            status: CouponStatus::Active,
+            audit_status: AuditStatus::Approved,
+            ..template_model()
+        }
+    }
+
+    #[test]
+    fn claims_next_receive_count_within_limit() {
+        let now = Utc::now();
+        let template = model(10, 2);
+        assert_eq!(check_claim(&template, 0, now).unwrap(), 1);
+        assert_eq!(check_claim(&template, 1, now).unwrap(), 2);
+
+        let err = check_claim(&template, 2, now).unwrap_err();
+        assert_eq!(err.code(), "A000502");
+        let err = check_claim(&model(0, 2), 0, now).unwrap_err();
+        assert_eq!(err.code(), "A000501");
+    }
+
+    #[test]
+    fn rejects_templates_outside_validity_window() {
+        let now = Utc::now();
+        let not_started = template::Model {
+            valid_start_time: Some(now + Duration::hours(1)),
+            ..model(10, 1)
+        };
+        let expired = template::Model {
+            valid_end_time: Some(now),
+            ..model(10, 1)
+        };
+        let pending_audit = template::Model {
+            audit_status: AuditStatus::PendingReview,
+            ..model(10, 1)
+        };
+        for template in [not_started, expired, pending_audit] {
+            assert_eq!(
+                check_claim(&template, 0, now).unwrap_err().code(),
+                "A000400"
+            );
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::auth::USER_ID;
+use crate::AppState;
+use actix_web::web::Data;
+use common::app_error::AppError;
+use data::dao::user_popup::user_popup_dao;
+use data::entity::user_popup;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::TransactionTrait;
+
+#[async_trait]
+pub trait UserPopupService: Send + Sync {
+    async fn take_popups(
+        &self,
+        app_state: Data<AppState>,
+    ) -> Result<Vec<user_popup::Model>, AppError>;
+}
+
+pub struct UserPopupServiceImpl;
+
+#[async_trait]
+impl UserPopupService for UserPopupServiceImpl {
+    /// 取走当前用户待弹出的弹框消息
+    ///
+    /// 最多返回配置数量的最新消息，按推送顺序排列；返回的消息和超过数量上限的更早消息都标记为已弹出，
+    /// 用户长时间不在线时只弹出最新的消息
+    ///
+    /// # 参数
+    /// * `app_state` - 应用程序状态，包含数据库连接和配置
+    ///
+    /// # 返回
+    /// * `Result<Vec<user_popup::Model>, AppError>` - 成功时返回待弹出的消息，没有时返回空列表
+    async fn take_popups(
+        &self,
+        app_state: Data<AppState>,
+    ) -> Result<Vec<user_popup::Model>, AppError> {
+        let user_id = USER_ID; //TODO: 需要实现用户登录模块
+        let capacity = app_state.config.notify.popup_capacity.max(1) as u64;
+
+        let txn = app_state.database.begin().await?;
+        let mut popups = user_popup_dao()
+            .list_pending_for_update(&txn, user_id, capacity)
+            .await?;
+        if let Some(latest) = popups.first() {
+            user_popup_dao()
+                .mark_popped(&txn, user_id, latest.id)
+                .await?;
+        }
+        txn.commit().await?;
+
+        popups.reverse();
+        Ok(popups)
+    }
+}
+
+static USER_POPUP_SERVICE: Lazy<UserPopupServiceImpl> = Lazy::new(|| UserPopupServiceImpl);
+
+pub fn user_popup_service() -> &'static dyn UserPopupService {
+    &*USER_POPUP_SERVICE
+}
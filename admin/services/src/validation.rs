@@ -0,0 +1,100 @@
+use common::app_error::{AppError, FieldError};
+use data::rule::RuleViolation;
+use rust_decimal::Decimal;
+
+/// 金额上限，单位为分，与数据库中金额字段 `decimal(10, 2)` 能保存的最大值一致
+const MAX_AMOUNT_CENTS: i64 = 9_999_999_999;
+
+/// 声明式请求参数校验
+///
+/// DTO 在 `rules` 中声明每个字段的校验规则，`validate` 会执行全部规则，
+/// 一次性返回所有不通过的字段，而不是遇到第一个错误就停止
+pub trait Validate {
+    /// 声明校验规则
+    fn rules(&self, v: &mut Validator);
+
+    /// 执行全部校验规则
+    fn validate(&self) -> Result<(), AppError> {
+        let mut validator = Validator::new();
+        self.rules(&mut validator);
+        validator.finish()
+    }
+}
+
+/// 校验错误收集器
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    /// 条件不成立时记录字段错误
+    pub fn check(&mut self, field: &str, ok: bool, message: impl Into<String>) -> &mut Self {
+        if !ok {
+            self.errors.push(FieldError::new(field, message));
+        }
+        self
+    }
+
+    /// 字符串不能为空白
+    pub fn not_blank(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(field, !value.trim().is_empty(), "不能为空")
+    }
+
+    /// 字符串长度 (按字符计) 不能超过上限
+    pub fn max_chars(&mut self, field: &str, value: &str, max: usize) -> &mut Self {
+        self.check(
+            field,
+            value.chars().count() <= max,
+            format!("长度不能超过 {} 个字符", max),
+        )
+    }
+
+    /// 可选字段必须有值
+    pub fn required<T>(&mut self, field: &str, value: &Option<T>) -> &mut Self {
+        self.check(field, value.is_some(), "不能为空")
+    }
+
+    /// 数值必须大于0
+    pub fn positive(&mut self, field: &str, value: i64) -> &mut Self {
+        self.check(field, value > 0, "必须大于0")
+    }
+
+    /// 金额不能小于0，不能超过 `MAX_AMOUNT_CENTS`，且最多保留两位小数
+    ///
+    /// 限制上限后，金额的合计和折扣计算不会溢出
+    pub fn amount(&mut self, field: &str, value: Decimal) -> &mut Self {
+        let max = Decimal::new(MAX_AMOUNT_CENTS, 2);
+        if value < Decimal::ZERO {
+            self.check(field, false, "金额不能小于0")
+        } else if value > max {
+            self.check(field, false, format!("金额不能超过{}", max))
+        } else {
+            self.check(
+                field,
+                value.normalize().scale() <= 2,
+                "金额最多保留两位小数",
+            )
+        }
+    }
+
+    /// 合并规则解析或校验产生的错误
+    pub fn violations(&mut self, violations: Vec<RuleViolation>) -> &mut Self {
+        self.errors
+            .extend(violations.into_iter().map(FieldError::from));
+        self
+    }
+
+    /// 结束校验，有任何不通过的字段时返回包含全部字段的校验错误
+    pub fn finish(self) -> Result<(), AppError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::validation_errors(self.errors))
+        }
+    }
+}
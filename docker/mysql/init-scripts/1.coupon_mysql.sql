@@ -23,7 +23,12 @@ CREATE TABLE `t_coupon_template`
     `status`           tinyint(1)   DEFAULT NULL COMMENT '优惠券状态 0：生效中 1：已结束',
     `create_time`      datetime     DEFAULT NULL COMMENT '创建时间',
     `update_time`      datetime     DEFAULT NULL COMMENT '修改时间',
-    `del_flag`         tinyint(1)   DEFAULT NULL COMMENT '删除标识 0：未删除 1：已删除',
+    `del_flag`         tinyint(1)   NOT NULL DEFAULT 0 COMMENT '删除标识 0：未删除 1：已删除',
+    `delete_time`      datetime     DEFAULT NULL COMMENT '删除时间',
+    `audit_status`     tinyint(1)   NOT NULL DEFAULT 2 COMMENT '审核状态 0：草稿 1：待审核 2：审核通过 3：审核驳回',
+    `reviewer_id`      bigint(20)   DEFAULT NULL COMMENT '审核人ID',
+    `audit_remark`     varchar(256) DEFAULT NULL COMMENT '审核意见',
+    `audit_time`       datetime     DEFAULT NULL COMMENT '审核时间',
     PRIMARY KEY (`id`),
     KEY `idx_shop_number` (`shop_number`) USING BTREE
 ) ENGINE = InnoDB
@@ -45,10 +50,11 @@ CREATE TABLE `t_user_coupon`
     `status`             tinyint(1) DEFAULT NULL COMMENT '状态 0：未使用 1：锁定 2：已使用 3：已过期 4：已撤回',
     `create_time`        datetime   DEFAULT NULL COMMENT '创建时间',
     `update_time`        datetime   DEFAULT NULL COMMENT '修改时间',
-    `del_flag`           tinyint(1) DEFAULT NULL COMMENT '删除标识 0：未删除 1：已删除',
+    `del_flag`           tinyint(1) NOT NULL DEFAULT 0 COMMENT '删除标识 0：未删除 1：已删除',
     PRIMARY KEY (`id`),
     UNIQUE KEY `idx_user_id_coupon_template_receive_count` (`user_id`, `coupon_template_id`, `receive_count`) USING BTREE,
-    KEY `idx_user_id` (`user_id`) USING BTREE
+    KEY `idx_user_id` (`user_id`) USING BTREE,
+    KEY `idx_coupon_template_id_status` (`coupon_template_id`, `status`) USING BTREE
 ) ENGINE = InnoDB
   AUTO_INCREMENT = 1815640588360376337
   DEFAULT CHARSET = utf8mb4 COMMENT ='用户优惠券表';
@@ -60,10 +66,12 @@ CREATE TABLE `t_coupon_settlement`
     `user_id`     bigint(20) DEFAULT NULL COMMENT '用户ID',
     `coupon_id`   bigint(20) DEFAULT NULL COMMENT '优惠券ID',
     `status`      int(11) DEFAULT NULL COMMENT '结算单状态 0：锁定 1：已取消 2：已支付 3：已退款',
+    `discount_amount` decimal(10, 2) DEFAULT NULL COMMENT '优惠金额',
     `create_time` datetime DEFAULT NULL COMMENT '创建时间',
     `update_time` datetime DEFAULT NULL COMMENT '修改时间',
     PRIMARY KEY (`id`),
-    KEY           `idx_user_id` (`user_id`) USING BTREE
+    KEY           `idx_user_id` (`user_id`) USING BTREE,
+    KEY           `idx_coupon_id` (`coupon_id`) USING BTREE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='优惠券结算单表';
 CREATE TABLE `t_coupon_template_remind`
 (
@@ -83,8 +91,8 @@ CREATE TABLE `t_coupon_template_log`
     `coupon_template_id` bigint(20)    DEFAULT NULL COMMENT '优惠券模板ID',
     `operator_id`        bigint(20)    DEFAULT NULL COMMENT '操作人',
     `operation_log`      text COMMENT '操作日志',
-    `original_data`      varchar(1024) DEFAULT NULL COMMENT '原始数据',
-    `modified_data`      varchar(1024) DEFAULT NULL COMMENT '修改后数据',
+    `original_data`      text COMMENT '原始数据',
+    `modified_data`      text COMMENT '修改后数据',
     `create_time`        datetime      DEFAULT NULL COMMENT '创建时间',
     PRIMARY KEY (`id`),
     KEY `idx_shop_number` (`shop_number`) USING BTREE
@@ -99,7 +107,12 @@ CREATE TABLE `t_coupon_task`
     `task_name`          varchar(128) DEFAULT NULL COMMENT '优惠券批次任务名称',
     `file_address`       varchar(512) DEFAULT NULL COMMENT '文件地址',
     `fail_file_address`  varchar(512) DEFAULT NULL COMMENT '发放失败用户文件地址',
+    `file_cleaned`       tinyint(1)   NOT NULL DEFAULT 0 COMMENT '用户文件是否已清理 0：未清理 1：已清理',
     `send_num`           int(11)      DEFAULT NULL COMMENT '发放优惠券数量',
+    `processed_num`      int(11)      NOT NULL DEFAULT 0 COMMENT '已处理用户数量',
+    `success_num`        int(11)      NOT NULL DEFAULT 0 COMMENT '发放成功数量',
+    `fail_num`           int(11)      NOT NULL DEFAULT 0 COMMENT '发放失败数量',
+    `retry_fail_id`      bigint(20)   DEFAULT NULL COMMENT '重试的最后一条发放失败记录ID',
     `notify_type`        varchar(32)  DEFAULT NULL COMMENT '通知方式，可组合使用 0：站内信 1：弹框推送 2：邮箱 3：短信',
     `coupon_template_id` bigint(20)   DEFAULT NULL COMMENT '优惠券模板ID',
     `send_type`          tinyint(1)   DEFAULT NULL COMMENT '发送类型 0：立即发送 1：定时发送',
@@ -109,10 +122,12 @@ CREATE TABLE `t_coupon_task`
     `create_time`        datetime     DEFAULT NULL COMMENT '创建时间',
     `operator_id`        bigint(20)   DEFAULT NULL COMMENT '操作人',
     `update_time`        datetime     DEFAULT NULL COMMENT '修改时间',
-    `del_flag`           tinyint(1)   DEFAULT NULL COMMENT '删除标识 0：未删除 1：已删除',
+    `del_flag`           tinyint(1)   NOT NULL DEFAULT 0 COMMENT '删除标识 0：未删除 1：已删除',
     PRIMARY KEY (`id`),
     KEY `idx_batch_id` (`batch_id`) USING BTREE,
-    KEY `idx_coupon_template_id` (`coupon_template_id`) USING BTREE
+    KEY `idx_coupon_template_id` (`coupon_template_id`) USING BTREE,
+    KEY `idx_status_send_time` (`status`, `send_time`) USING BTREE,
+    KEY `idx_completion_time` (`completion_time`) USING BTREE
 ) ENGINE = InnoDB
   AUTO_INCREMENT = 1816672964423188483
   DEFAULT CHARSET = utf8mb4 COMMENT ='优惠券模板发送任务表';
@@ -126,6 +141,64 @@ CREATE TABLE `t_coupon_task_fail`
     KEY `idx_batch_id` (`batch_id`) USING BTREE
 ) ENGINE = InnoDB
   DEFAULT CHARSET = utf8mb4 COMMENT ='优惠券模板发送任务失败记录表';
+##################################################################################################
+CREATE TABLE `t_coupon_notify_fail`
+(
+    `id`              bigint(20)   NOT NULL AUTO_INCREMENT COMMENT 'ID',
+    `batch_id`        bigint(20)   DEFAULT NULL COMMENT '批次ID',
+    `user_id`         bigint(20)   DEFAULT NULL COMMENT '用户ID',
+    `notify_type`     tinyint(1)   DEFAULT NULL COMMENT '通知方式 0：站内信 1：弹框推送 2：邮箱 3：短信',
+    `title`           varchar(128) DEFAULT NULL COMMENT '通知标题',
+    `content`         varchar(512) DEFAULT NULL COMMENT '通知内容',
+    `attempts`        int(11)      DEFAULT NULL COMMENT '已尝试发送的次数',
+    `reason`          varchar(512) DEFAULT NULL COMMENT '最后一次失败的原因',
+    `status`          tinyint(1)   NOT NULL DEFAULT 0 COMMENT '状态 0：等待重试 1：已放弃',
+    `next_retry_time` datetime     DEFAULT NULL COMMENT '下次重试时间',
+    `create_time`     datetime     DEFAULT NULL COMMENT '创建时间',
+    `update_time`     datetime     DEFAULT NULL COMMENT '修改时间',
+    PRIMARY KEY (`id`),
+    KEY `idx_batch_id` (`batch_id`) USING BTREE,
+    KEY `idx_status_next_retry_time` (`status`, `next_retry_time`) USING BTREE
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4 COMMENT ='优惠券发放通知失败记录表';
+##################################################################################################
+CREATE TABLE `t_user_message`
+(
+    `id`          bigint(20)   NOT NULL AUTO_INCREMENT COMMENT 'ID',
+    `user_id`     bigint(20)   DEFAULT NULL COMMENT '用户ID',
+    `title`       varchar(128) DEFAULT NULL COMMENT '标题',
+    `content`     varchar(512) DEFAULT NULL COMMENT '内容',
+    `read_flag`   tinyint(1)   DEFAULT NULL COMMENT '已读标识 0：未读 1：已读',
+    `read_time`   datetime     DEFAULT NULL COMMENT '阅读时间',
+    `create_time` datetime     DEFAULT NULL COMMENT '创建时间',
+    `del_flag`    tinyint(1)   NOT NULL DEFAULT 0 COMMENT '删除标识 0：未删除 1：已删除',
+    PRIMARY KEY (`id`),
+    KEY `idx_user_id` (`user_id`) USING BTREE
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4 COMMENT ='用户站内信表';
+##################################################################################################
+CREATE TABLE `t_user_popup`
+(
+    `id`          bigint(20)   NOT NULL AUTO_INCREMENT COMMENT 'ID',
+    `user_id`     bigint(20)   DEFAULT NULL COMMENT '用户ID',
+    `title`       varchar(128) DEFAULT NULL COMMENT '标题',
+    `content`     varchar(512) DEFAULT NULL COMMENT '内容',
+    `popped_flag` tinyint(1)   NOT NULL DEFAULT 0 COMMENT '已弹出标识 0：未弹出 1：已弹出',
+    `create_time` datetime     DEFAULT NULL COMMENT '创建时间',
+    PRIMARY KEY (`id`),
+    KEY `idx_user_id_popped_flag` (`user_id`, `popped_flag`) USING BTREE
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4 COMMENT ='用户弹框消息表';
+##################################################################################################
+CREATE TABLE `t_user_contact`
+(
+    `user_id`     bigint(20)   NOT NULL COMMENT '用户ID',
+    `phone`       varchar(32)  DEFAULT NULL COMMENT '手机号',
+    `email`       varchar(128) DEFAULT NULL COMMENT '邮箱',
+    `update_time` datetime     DEFAULT NULL COMMENT '修改时间',
+    PRIMARY KEY (`user_id`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4 COMMENT ='用户联系方式表，由用户服务同步';
 
 
 ########################################################################################################################
\ No newline at end of file